@@ -1,3 +1,4 @@
+#![allow(clippy::unwrap_used)]
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use m_bus_parser::frames::Frame;
 
@@ -1,3 +1,4 @@
+#![allow(clippy::unwrap_used)]
 use m_bus_parser::MbusData;
 fn main() {
     let example = vec![
@@ -1,3 +1,4 @@
+#![allow(clippy::unwrap_used)]
 use m_bus_parser::frames::{Address, Frame, Function};
 ///       68 4d 4d 68 08 01 72 01 00 00 00 96 15 01 00 18 00 00 00 0c 78 56 00 00 00 01
 ///       fd 1b 00 02 fc 03 48 52 25 74 44 0d 22 fc 03 48 52 25 74 f1 0c 12 fc 03 48 52
@@ -63,8 +64,8 @@ fn main() {
         {
             println!("fixed_data_header: {:#?}", fixed_data_header);
             println!("variable_data_block: {:?}", variable_data_block);
-            let data_records = m_bus_parser::user_data::DataRecords::try_from(variable_data_block);
-            println!("data_records: {:#?}", data_records.unwrap());
+            let data_records = m_bus_parser::user_data::DataRecords::from(variable_data_block);
+            println!("data_records: {:#?}", data_records);
         }
     }
 }
@@ -105,8 +106,8 @@ fn main() {
         {
             println!("fixed_data_header: {:#?}", fixed_data_header);
             println!("variable_data_block: {:?}", variable_data_block);
-            let data_records = m_bus_parser::user_data::DataRecords::try_from(variable_data_block);
-            println!("data_records: {:#?}", data_records.unwrap());
+            let data_records = m_bus_parser::user_data::DataRecords::from(variable_data_block);
+            println!("data_records: {:#?}", data_records);
         }
     }
 }
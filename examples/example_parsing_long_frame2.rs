@@ -1,3 +1,4 @@
+#![allow(clippy::unwrap_used)]
 use m_bus_parser::frames::{Address, Frame, Function};
 fn main() {
     let example = vec![
@@ -31,8 +32,8 @@ fn main() {
         {
             println!("fixed_data_header: {:#?}", fixed_data_header);
             println!("variable_data_block: {:?}", variable_data_block);
-            let data_records = m_bus_parser::user_data::DataRecords::try_from(variable_data_block);
-            println!("data_records: {:#?}", data_records.unwrap());
+            let data_records = m_bus_parser::user_data::DataRecords::from(variable_data_block);
+            println!("data_records: {:#?}", data_records);
         }
     }
 }
@@ -0,0 +1,610 @@
+//! Authentication and fragmentation layer (AFL, CI 0x90) of EN 13757-7 and OMS
+//!
+//! The AFL precedes the transport layer. It carries the message counter and the MAC of
+//! authenticated messages and allows to split long application messages into fragments,
+//! which are stitched back together by the [`FragmentReassembler`].
+use arrayvec::ArrayVec;
+
+#[cfg(feature = "decryption")]
+use crate::security::Authentication;
+
+/// CI field of the authentication and fragmentation layer
+pub const AFL_CONTROL_INFORMATION: u8 = 0x90;
+
+/// Longest application message that can be reassembled by default
+pub const DEFAULT_MESSAGE_CAPACITY: usize = 1024;
+
+/// Message control, key information, message counter and message length
+const MAX_AUTHENTICATED_LENGTH: usize = 9;
+
+const MAX_MAC_LENGTH: usize = 16;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AflError {
+    LengthShort,
+    /// The AFL length field does not match the fields present
+    WrongLength {
+        expected: usize,
+        actual: usize,
+    },
+    /// A MAC is present but its length is unknown for the authentication type
+    UnknownMacLength {
+        authentication_type: u8,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for AflError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AflError::LengthShort => write!(f, "Length short"),
+            AflError::WrongLength { expected, actual } => {
+                write!(
+                    f,
+                    "Wrong AFL length: expected {}, actual {}",
+                    expected, actual
+                )
+            }
+            AflError::UnknownMacLength {
+                authentication_type,
+            } => write!(
+                f,
+                "Unknown MAC length for authentication type {}",
+                authentication_type
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AflError {}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AuthenticationType {
+    None,
+    AesCmac4,
+    AesCmac8,
+    AesCmac12,
+    AesCmac16,
+    AesGmac12,
+    Reserved(u8),
+}
+
+impl AuthenticationType {
+    #[must_use]
+    pub const fn from_bits(bits: u8) -> Self {
+        match bits & 0x0F {
+            0 => Self::None,
+            4 => Self::AesCmac4,
+            5 => Self::AesCmac8,
+            6 => Self::AesCmac12,
+            7 => Self::AesCmac16,
+            8 => Self::AesGmac12,
+            bits => Self::Reserved(bits),
+        }
+    }
+
+    /// Length of the truncated MAC in bytes
+    #[must_use]
+    pub const fn mac_length(&self) -> Option<usize> {
+        match self {
+            Self::AesCmac4 => Some(4),
+            Self::AesCmac8 => Some(8),
+            Self::AesCmac12 | Self::AesGmac12 => Some(12),
+            Self::AesCmac16 => Some(16),
+            Self::None | Self::Reserved(_) => None,
+        }
+    }
+}
+
+/// Message control field (AFL.MCL), describes the message as a whole
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MessageControl {
+    pub message_length_present: bool,
+    pub message_counter_present: bool,
+    pub key_information_present: bool,
+    pub authentication_type: AuthenticationType,
+}
+
+impl From<u8> for MessageControl {
+    fn from(byte: u8) -> Self {
+        Self {
+            message_length_present: byte & 0x40 != 0,
+            message_counter_present: byte & 0x20 != 0,
+            key_information_present: byte & 0x10 != 0,
+            authentication_type: AuthenticationType::from_bits(byte),
+        }
+    }
+}
+
+/// Authentication and fragmentation layer of one fragment
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AuthenticationFragmentationLayer<'a> {
+    /// Further fragments of the message follow
+    pub more_fragments: bool,
+    pub fragment_id: u8,
+    pub message_control: Option<MessageControl>,
+    pub key_information: Option<u16>,
+    /// Message counter (AFL.MCR), strictly increasing for every message of a meter, which
+    /// allows to detect replayed messages
+    pub message_counter: Option<u32>,
+    pub mac: Option<&'a [u8]>,
+    /// Length of the whole application message, starting with the CI field of the transport
+    /// layer
+    pub message_length: Option<u16>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    authenticated: ArrayVec<u8, MAX_AUTHENTICATED_LENGTH>,
+}
+
+impl<'a> AuthenticationFragmentationLayer<'a> {
+    /// Parses the AFL at the start of `data`, returns it together with the remaining data of
+    /// the fragment. Data without an AFL is returned unchanged.
+    pub fn parse(data: &'a [u8]) -> Result<(Option<Self>, &'a [u8]), AflError> {
+        if data.first() != Some(&AFL_CONTROL_INFORMATION) {
+            return Ok((None, data));
+        }
+        let length = usize::from(*data.get(1).ok_or(AflError::LengthShort)?);
+        let fields = data.get(2..2 + length).ok_or(AflError::LengthShort)?;
+        let afl = Self::parse_fields(fields)?;
+        Ok((
+            Some(afl),
+            data.get(2 + length..).ok_or(AflError::LengthShort)?,
+        ))
+    }
+
+    fn parse_fields(fields: &'a [u8]) -> Result<Self, AflError> {
+        let mut offset = 0;
+        let mut take = |count: usize| {
+            let bytes = fields
+                .get(offset..offset + count)
+                .ok_or(AflError::LengthShort)?;
+            offset += count;
+            Ok::<_, AflError>(bytes)
+        };
+        let mut authenticated = ArrayVec::new();
+
+        let fragmentation_control =
+            u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?).map_err(|_| AflError::LengthShort)?);
+        let present = |bit: u16| fragmentation_control & (1 << bit) != 0;
+
+        let message_control = if present(13) {
+            let byte = take(1)?;
+            authenticated.extend(byte.iter().copied());
+            byte.first().map(|&byte| MessageControl::from(byte))
+        } else {
+            None
+        };
+        let key_information = if present(9) {
+            let bytes = take(2)?;
+            authenticated.extend(bytes.iter().copied());
+            Some(u16::from_le_bytes(
+                <[u8; 2]>::try_from(bytes).map_err(|_| AflError::LengthShort)?,
+            ))
+        } else {
+            None
+        };
+        let message_counter = if present(11) {
+            let bytes = take(4)?;
+            authenticated.extend(bytes.iter().copied());
+            Some(u32::from_le_bytes(
+                <[u8; 4]>::try_from(bytes).map_err(|_| AflError::LengthShort)?,
+            ))
+        } else {
+            None
+        };
+        let mac = if present(10) {
+            let authentication_type = message_control
+                .map(|control| control.authentication_type)
+                .unwrap_or(AuthenticationType::None);
+            let length = authentication_type
+                .mac_length()
+                .ok_or(AflError::UnknownMacLength {
+                    authentication_type: match authentication_type {
+                        AuthenticationType::Reserved(bits) => bits,
+                        _ => 0,
+                    },
+                })?;
+            Some(take(length)?)
+        } else {
+            None
+        };
+        let message_length = if present(12) {
+            let bytes = take(2)?;
+            authenticated.extend(bytes.iter().copied());
+            Some(u16::from_le_bytes(
+                <[u8; 2]>::try_from(bytes).map_err(|_| AflError::LengthShort)?,
+            ))
+        } else {
+            None
+        };
+
+        if offset != fields.len() {
+            return Err(AflError::WrongLength {
+                expected: offset,
+                actual: fields.len(),
+            });
+        }
+
+        Ok(Self {
+            more_fragments: present(14),
+            fragment_id: fragmentation_control as u8,
+            message_control,
+            key_information,
+            message_counter,
+            mac,
+            message_length,
+            authenticated,
+        })
+    }
+
+    /// AFL fields covered by the MAC in the order they enter the MAC calculation
+    #[must_use]
+    pub fn authenticated_fields(&self) -> &[u8] {
+        &self.authenticated
+    }
+
+    /// Authentication of the unfragmented `message` following this AFL, `None` without
+    /// message counter or MAC
+    #[cfg(feature = "decryption")]
+    #[must_use]
+    pub fn authentication<'b>(&'b self, message: &'b [u8]) -> Option<Authentication<'b>> {
+        Some(Authentication {
+            fields: &self.authenticated,
+            message_counter: self.message_counter?,
+            mac: self.mac?,
+            message,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReassemblyError {
+    AflError(AflError),
+    /// Fragments have to carry an AFL
+    MissingAfl,
+    /// A fragment is missing, the message has to be received again
+    MissingFragment {
+        expected: u8,
+        received: u8,
+    },
+    /// The reassembled message does not have the length announced in the first fragment
+    MessageLengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    BufferFull {
+        required: usize,
+    },
+    /// The last fragment was already received, call `reset` for a new message
+    AlreadyComplete,
+}
+
+impl From<AflError> for ReassemblyError {
+    fn from(error: AflError) -> Self {
+        Self::AflError(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for ReassemblyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReassemblyError::AflError(error) => write!(f, "{}", error),
+            ReassemblyError::MissingAfl => {
+                write!(f, "Missing authentication and fragmentation layer")
+            }
+            ReassemblyError::MissingFragment { expected, received } => {
+                write!(
+                    f,
+                    "Missing fragment: expected {}, received {}",
+                    expected, received
+                )
+            }
+            ReassemblyError::MessageLengthMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Message length mismatch: expected {}, actual {}",
+                    expected, actual
+                )
+            }
+            ReassemblyError::BufferFull { required } => {
+                write!(f, "Buffer full, required: {}", required)
+            }
+            ReassemblyError::AlreadyComplete => write!(f, "Message already complete"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReassemblyError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FragmentProgress {
+    MoreFragments,
+    Complete,
+}
+
+/// Stitches the fragments of one application message back together. The first fragment
+/// carries the message counter and the MAC of the whole message.
+#[derive(Debug)]
+pub struct FragmentReassembler<const N: usize = DEFAULT_MESSAGE_CAPACITY> {
+    fragment_id: Option<u8>,
+    message_counter: Option<u32>,
+    message_length: Option<u16>,
+    mac: ArrayVec<u8, MAX_MAC_LENGTH>,
+    authenticated: ArrayVec<u8, MAX_AUTHENTICATED_LENGTH>,
+    complete: bool,
+    message: ArrayVec<u8, N>,
+}
+
+impl<const N: usize> Default for FragmentReassembler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> FragmentReassembler<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fragment_id: None,
+            message_counter: None,
+            message_length: None,
+            mac: ArrayVec::new_const(),
+            authenticated: ArrayVec::new_const(),
+            complete: false,
+            message: ArrayVec::new_const(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Adds the next fragment, starting with the CI field of the AFL. A missing fragment
+    /// discards the message received so far. A message has to start with fragment 1 or a
+    /// fragment carrying the message control or message counter.
+    pub fn push(&mut self, fragment: &[u8]) -> Result<FragmentProgress, ReassemblyError> {
+        if self.complete {
+            return Err(ReassemblyError::AlreadyComplete);
+        }
+        let (Some(afl), data) = AuthenticationFragmentationLayer::parse(fragment)? else {
+            return Err(ReassemblyError::MissingAfl);
+        };
+
+        match self.fragment_id {
+            None => {
+                if afl.fragment_id != 1
+                    && afl.message_control.is_none()
+                    && afl.message_counter.is_none()
+                {
+                    return Err(ReassemblyError::MissingFragment {
+                        expected: 1,
+                        received: afl.fragment_id,
+                    });
+                }
+                self.message_counter = afl.message_counter;
+                self.message_length = afl.message_length;
+                self.authenticated = afl.authenticated.clone();
+                self.mac.clear();
+                if let Some(mac) = afl.mac {
+                    let _ = self.mac.try_extend_from_slice(mac);
+                }
+            }
+            Some(previous) => {
+                let expected = previous.wrapping_add(1);
+                if afl.fragment_id != expected {
+                    self.reset();
+                    return Err(ReassemblyError::MissingFragment {
+                        expected,
+                        received: afl.fragment_id,
+                    });
+                }
+            }
+        }
+        self.message
+            .try_extend_from_slice(data)
+            .map_err(|_| ReassemblyError::BufferFull {
+                required: self.message.len() + data.len(),
+            })?;
+        self.fragment_id = Some(afl.fragment_id);
+
+        if afl.more_fragments {
+            return Ok(FragmentProgress::MoreFragments);
+        }
+        if let Some(expected) = self.message_length.map(usize::from) {
+            if expected != self.message.len() {
+                let actual = self.message.len();
+                self.reset();
+                return Err(ReassemblyError::MessageLengthMismatch { expected, actual });
+            }
+        }
+        self.complete = true;
+        Ok(FragmentProgress::Complete)
+    }
+
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Message counter of the first fragment
+    #[must_use]
+    pub const fn message_counter(&self) -> Option<u32> {
+        self.message_counter
+    }
+
+    /// The application message received so far, starting with the CI field of the transport
+    /// layer
+    #[must_use]
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Authentication of the complete message, `None` before the last fragment or without
+    /// message counter or MAC
+    #[cfg(feature = "decryption")]
+    #[must_use]
+    pub fn authentication(&self) -> Option<Authentication<'_>> {
+        if !self.complete || self.mac.is_empty() {
+            return None;
+        }
+        Some(Authentication {
+            fields: &self.authenticated,
+            message_counter: self.message_counter?,
+            mac: &self.mac,
+            message: &self.message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // unfragmented message with message control, message counter 0x12 and an 8 byte MAC
+    const AFL: [u8; 18] = [
+        0x90, 0x0F, 0x00, 0x2C, 0x25, 0x12, 0x00, 0x00, 0x00, 0x02, 0xAF, 0xBD, 0xF9, 0x45, 0x15,
+        0x97, 0x35, 0x72,
+    ];
+
+    #[test]
+    fn test_parse_afl() {
+        let (Some(afl), rest) = AuthenticationFragmentationLayer::parse(&AFL).unwrap() else {
+            panic!("expected an AFL");
+        };
+        assert_eq!(rest, &[0x72]);
+        assert!(!afl.more_fragments);
+        assert_eq!(afl.fragment_id, 0);
+        assert_eq!(
+            afl.message_control,
+            Some(MessageControl {
+                message_length_present: false,
+                message_counter_present: true,
+                key_information_present: false,
+                authentication_type: AuthenticationType::AesCmac8,
+            })
+        );
+        assert_eq!(afl.key_information, None);
+        assert_eq!(afl.message_counter, Some(0x12));
+        assert_eq!(afl.mac, Some(&AFL[9..17]));
+        assert_eq!(afl.message_length, None);
+        assert_eq!(afl.authenticated_fields(), &[0x25, 0x12, 0x00, 0x00, 0x00]);
+
+        assert_eq!(
+            AuthenticationFragmentationLayer::parse(&[0x72, 0x01]),
+            Ok((None, [0x72, 0x01].as_slice()))
+        );
+        assert_eq!(
+            AuthenticationFragmentationLayer::parse(&AFL[..10]),
+            Err(AflError::LengthShort)
+        );
+        let mut afl = AFL;
+        afl[1] = 0x10;
+        assert_eq!(
+            AuthenticationFragmentationLayer::parse(&afl),
+            Err(AflError::WrongLength {
+                expected: 15,
+                actual: 16
+            })
+        );
+        afl[1] = 0x0F;
+        afl[4] = 0x22;
+        assert_eq!(
+            AuthenticationFragmentationLayer::parse(&afl),
+            Err(AflError::UnknownMacLength {
+                authentication_type: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_reassemble_fragments() {
+        // first fragment with message control, message counter and message length 7
+        let first = [
+            0x90, 0x09, 0x01, 0x78, 0x60, 0x07, 0x00, 0x00, 0x00, 0x07, 0x00, 0x72, 0x78, 0x56,
+        ];
+        let second = [0x90, 0x02, 0x02, 0x40, 0x34, 0x12];
+        let last = [0x90, 0x02, 0x03, 0x00, 0xE6, 0x1E];
+
+        let mut reassembler = FragmentReassembler::<16>::new();
+        assert_eq!(
+            reassembler.push(&first),
+            Ok(FragmentProgress::MoreFragments)
+        );
+        assert_eq!(reassembler.message_counter(), Some(7));
+        assert_eq!(
+            reassembler.push(&second),
+            Ok(FragmentProgress::MoreFragments)
+        );
+        assert_eq!(reassembler.push(&last), Ok(FragmentProgress::Complete));
+        assert!(reassembler.is_complete());
+        assert_eq!(
+            reassembler.message(),
+            &[0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E]
+        );
+        assert_eq!(
+            reassembler.push(&last),
+            Err(ReassemblyError::AlreadyComplete)
+        );
+    }
+
+    #[test]
+    fn test_reassembly_errors() {
+        let first = [0x90, 0x04, 0x01, 0x50, 0x03, 0x00, 0x72, 0x78];
+        let second = [0x90, 0x02, 0x02, 0x40, 0x56];
+        let last = [0x90, 0x02, 0x03, 0x00, 0x34];
+
+        let mut reassembler = FragmentReassembler::<16>::new();
+        reassembler.push(&first).unwrap();
+        assert_eq!(
+            reassembler.push(&last),
+            Err(ReassemblyError::MissingFragment {
+                expected: 2,
+                received: 3
+            })
+        );
+        assert!(reassembler.message().is_empty());
+
+        // a message does not start with a later fragment
+        assert_eq!(
+            reassembler.push(&second),
+            Err(ReassemblyError::MissingFragment {
+                expected: 1,
+                received: 2
+            })
+        );
+        assert!(reassembler.message().is_empty());
+
+        reassembler.push(&first).unwrap();
+        reassembler.push(&second).unwrap();
+        assert_eq!(
+            reassembler.push(&last),
+            Err(ReassemblyError::MessageLengthMismatch {
+                expected: 3,
+                actual: 4
+            })
+        );
+
+        assert_eq!(
+            reassembler.push(&[0x72, 0x00]),
+            Err(ReassemblyError::MissingAfl)
+        );
+        let mut reassembler = FragmentReassembler::<2>::new();
+        assert_eq!(
+            reassembler.push(&[0x90, 0x02, 0x01, 0x00, 0x72, 0x78, 0x56]),
+            Err(ReassemblyError::BufferFull { required: 3 })
+        );
+    }
+}
@@ -0,0 +1,655 @@
+//! Builds SND_UD frames for the common application commands of EN 13757-3
+//!
+//! A command is turned into the user data of a control frame and encoded together with the
+//! address of the slave, e.g. `SetPrimaryAddress(8)` sent to the broadcast address gives
+//! `68 06 06 68 53 FE 51 01 7A 08 25 16`.
+use arrayvec::ArrayVec;
+
+use crate::frames::{Address, Frame, FrameError, Function, MAX_FRAME_DATA_LENGTH};
+use crate::user_data::data_information::{DataFieldCoding, FunctionField};
+use crate::user_data::data_record::DataRecordHeader;
+use crate::user_data::{ApplicationResetSubcode, SecondaryAddress};
+
+const RESET_AT_APPLICATION_LEVEL: u8 = 0x50;
+const SEND_DATA: u8 = 0x51;
+const SELECT_SLAVE: u8 = 0x52;
+/// DIF 8 bit integer, VIF bus address
+const PRIMARY_ADDRESS_RECORD: [u8; 2] = [0x01, 0x7A];
+/// DIF 8 digit BCD, VIF enhanced identification
+const IDENTIFICATION_NUMBER_RECORD: [u8; 2] = [0x0C, 0x79];
+/// DIF 32 bit integer, VIF date and time (type F)
+const DATE_TIME_RECORD: [u8; 2] = [0x04, 0x6D];
+/// DIF with the data field for selection for readout
+const SELECTION_FOR_READOUT: u8 = 0x08;
+/// DIF requesting all records
+const GLOBAL_READOUT_REQUEST: u8 = 0x7F;
+const IDLE_FILLER: u8 = 0x2F;
+const MAX_DIFE: usize = 10;
+/// VIF and up to 10 VIFEs
+pub const MAX_SELECTION_VIB_LENGTH: usize = 11;
+pub const MAX_SELECTED_RECORDS: usize = 16;
+/// Largest user data of a command that fits into a control frame, the CI field and the
+/// records take all of the frame data since the C- and A-field are already excluded
+pub const MAX_COMMAND_LENGTH: usize = MAX_FRAME_DATA_LENGTH;
+const MAX_PRIMARY_ADDRESS: u8 = 250;
+const MAX_IDENTIFICATION_NUMBER: u32 = 99_999_999;
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CommandError {
+    InvalidPrimaryAddress {
+        address: u8,
+    },
+    InvalidIdentificationNumber {
+        number: u32,
+    },
+    InvalidDateTime,
+    /// Only the baud rates with a CI field of their own can be set
+    UnsupportedBaudRate {
+        baud_rate: u32,
+    },
+    /// Storage number, tariff or subunit need more than 10 DIFEs
+    SelectionOutOfRange,
+    /// The records are not a selection for readout
+    InvalidSelection,
+    CommandTooLong,
+    FrameError(FrameError),
+}
+
+impl From<FrameError> for CommandError {
+    fn from(error: FrameError) -> Self {
+        Self::FrameError(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::InvalidPrimaryAddress { address } => {
+                write!(f, "Invalid primary address: {}", address)
+            }
+            CommandError::InvalidIdentificationNumber { number } => {
+                write!(f, "Invalid identification number: {}", number)
+            }
+            CommandError::InvalidDateTime => write!(f, "Invalid date and time"),
+            CommandError::UnsupportedBaudRate { baud_rate } => {
+                write!(f, "Unsupported baud rate: {}", baud_rate)
+            }
+            CommandError::SelectionOutOfRange => write!(f, "Selection out of range"),
+            CommandError::InvalidSelection => write!(f, "Invalid selection for readout"),
+            CommandError::CommandTooLong => write!(f, "Command too long"),
+            CommandError::FrameError(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CommandError {}
+
+/// Date and time as sent in a type F record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DateTime {
+    /// Years since 2000, as decoded by `DataType::DateTime`
+    pub year: u8,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl DateTime {
+    fn to_type_f(self) -> Result<[u8; 4], CommandError> {
+        if self.year > 99
+            || !(1..=12).contains(&self.month)
+            || !(1..=31).contains(&self.day)
+            || self.hour > 23
+            || self.minute > 59
+        {
+            return Err(CommandError::InvalidDateTime);
+        }
+        Ok([
+            self.minute,
+            self.hour,
+            self.day | ((self.year & 0x07) << 5),
+            self.month | ((self.year & 0x78) << 1),
+        ])
+    }
+}
+
+/// A record the slave should send, e.g. the volume of storage 3
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecordSelection {
+    pub storage_number: u64,
+    pub tariff: u64,
+    /// Subunit of the slave
+    pub device: u64,
+    pub function_field: FunctionField,
+    /// VIF and VIFEs of the quantity
+    pub value_information: ArrayVec<u8, MAX_SELECTION_VIB_LENGTH>,
+}
+
+impl RecordSelection {
+    /// Selects the instantaneous value of storage 0, tariff 0 and subunit 0 for the quantity
+    pub fn new(value_information: &[u8]) -> Result<Self, CommandError> {
+        Ok(Self {
+            storage_number: 0,
+            tariff: 0,
+            device: 0,
+            function_field: FunctionField::InstantaneousValue,
+            value_information: ArrayVec::try_from(value_information)
+                .map_err(|_| CommandError::InvalidSelection)?,
+        })
+    }
+
+    fn write_into(
+        &self,
+        user_data: &mut ArrayVec<u8, MAX_COMMAND_LENGTH>,
+    ) -> Result<(), CommandError> {
+        // every DIFE adds 4 bits of storage number, 2 of tariff and 1 of subunit
+        let mut difes = 0;
+        while difes < MAX_DIFE
+            && (self.storage_number >> (1 + 4 * difes) != 0
+                || self.tariff >> (2 * difes) != 0
+                || self.device >> difes != 0)
+        {
+            difes += 1;
+        }
+        if self.storage_number >> (1 + 4 * difes) != 0
+            || self.tariff >> (2 * difes) != 0
+            || self.device >> difes != 0
+        {
+            return Err(CommandError::SelectionOutOfRange);
+        }
+
+        let function_field = match self.function_field {
+            FunctionField::InstantaneousValue => 0b00,
+            FunctionField::MaximumValue => 0b01,
+            FunctionField::MinimumValue => 0b10,
+            FunctionField::ValueDuringErrorState => 0b11,
+        };
+        let extension = if difes > 0 { 0x80 } else { 0x00 };
+        let dif = extension
+            | ((self.storage_number & 0x01) as u8) << 6
+            | function_field << 4
+            | SELECTION_FOR_READOUT;
+        user_data
+            .try_push(dif)
+            .map_err(|_| CommandError::CommandTooLong)?;
+        for index in 0..difes {
+            let extension = if index + 1 < difes { 0x80 } else { 0x00 };
+            let dife = extension
+                | (((self.device >> index) & 0x01) as u8) << 6
+                | (((self.tariff >> (2 * index)) & 0x03) as u8) << 4
+                | ((self.storage_number >> (1 + 4 * index)) & 0x0F) as u8;
+            user_data
+                .try_push(dife)
+                .map_err(|_| CommandError::CommandTooLong)?;
+        }
+        user_data
+            .try_extend_from_slice(&self.value_information)
+            .map_err(|_| CommandError::CommandTooLong)
+    }
+}
+
+/// Records requested with a SND_UD before the next REQ_UD2
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadoutSelection {
+    /// All records of the slave (DIF 0x7F)
+    Global,
+    Records(ArrayVec<RecordSelection, MAX_SELECTED_RECORDS>),
+}
+
+/// Decodes the records of a SND_UD with CI 0x51 back into a selection
+impl TryFrom<&[u8]> for ReadoutSelection {
+    type Error = CommandError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let mut records = ArrayVec::new();
+        let mut offset = 0;
+        while let Some(record) = data.get(offset..).filter(|r| !r.is_empty()) {
+            match record.first() {
+                Some(&IDLE_FILLER) => {
+                    offset += 1;
+                    continue;
+                }
+                Some(&GLOBAL_READOUT_REQUEST) if records.is_empty() => {
+                    return Ok(Self::Global);
+                }
+                _ => {}
+            }
+            let header =
+                DataRecordHeader::try_from(record).map_err(|_| CommandError::InvalidSelection)?;
+            let size = header.get_size();
+            let dib_size = header
+                .raw_data_record_header
+                .data_information_block
+                .get_size();
+            let data_information = header
+                .processed_data_record_header
+                .data_information
+                .filter(|information| {
+                    information.data_field_coding == DataFieldCoding::SelectionForReadout
+                })
+                .ok_or(CommandError::InvalidSelection)?;
+            let value_information = record
+                .get(dib_size..size)
+                .ok_or(CommandError::InvalidSelection)?;
+            records
+                .try_push(RecordSelection {
+                    storage_number: data_information.storage_number,
+                    tariff: data_information.tariff,
+                    device: data_information.device,
+                    function_field: data_information.function_field,
+                    value_information: ArrayVec::try_from(value_information)
+                        .map_err(|_| CommandError::InvalidSelection)?,
+                })
+                .map_err(|_| CommandError::InvalidSelection)?;
+            offset += size;
+        }
+        if records.is_empty() {
+            return Err(CommandError::InvalidSelection);
+        }
+        Ok(Self::Records(records))
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command {
+    SetPrimaryAddress(u8),
+    /// Changes the identification number of the secondary address
+    SetIdentificationNumber(u32),
+    SetDateTime(DateTime),
+    ApplicationReset(ApplicationResetSubcode),
+    SetBaudRate(u32),
+    /// Has to be sent to `Address::Secondary`
+    SelectSecondary(SecondaryAddress),
+    SelectForReadout(ReadoutSelection),
+}
+
+impl Command {
+    /// The user data of the control frame, starting with the CI field
+    pub fn user_data(&self) -> Result<ArrayVec<u8, MAX_COMMAND_LENGTH>, CommandError> {
+        let mut user_data = ArrayVec::new();
+        match self {
+            Command::SetPrimaryAddress(address) => {
+                if *address > MAX_PRIMARY_ADDRESS {
+                    return Err(CommandError::InvalidPrimaryAddress { address: *address });
+                }
+                user_data.push(SEND_DATA);
+                user_data.extend(PRIMARY_ADDRESS_RECORD);
+                user_data.push(*address);
+            }
+            Command::SetIdentificationNumber(number) => {
+                if *number > MAX_IDENTIFICATION_NUMBER {
+                    return Err(CommandError::InvalidIdentificationNumber { number: *number });
+                }
+                user_data.push(SEND_DATA);
+                user_data.extend(IDENTIFICATION_NUMBER_RECORD);
+                user_data.extend(to_bcd(*number).to_le_bytes());
+            }
+            Command::SetDateTime(date_time) => {
+                user_data.push(SEND_DATA);
+                user_data.extend(DATE_TIME_RECORD);
+                user_data.extend(date_time.to_type_f()?);
+            }
+            Command::ApplicationReset(subcode) => {
+                user_data.push(RESET_AT_APPLICATION_LEVEL);
+                user_data.push(u8::from(subcode));
+            }
+            Command::SetBaudRate(baud_rate) => {
+                user_data.push(baud_rate_control_information(*baud_rate)?);
+            }
+            Command::SelectSecondary(secondary_address) => {
+                user_data.push(SELECT_SLAVE);
+                user_data.extend(secondary_address.to_bytes());
+            }
+            Command::SelectForReadout(ReadoutSelection::Global) => {
+                user_data.push(SEND_DATA);
+                user_data.push(GLOBAL_READOUT_REQUEST);
+            }
+            Command::SelectForReadout(ReadoutSelection::Records(records)) => {
+                user_data.push(SEND_DATA);
+                for record in records {
+                    record.write_into(&mut user_data)?;
+                }
+            }
+        }
+        Ok(user_data)
+    }
+
+    /// Writes the SND_UD frame for the slave at `address` into `buffer` and returns the number
+    /// of bytes written
+    pub fn encode_into(
+        &self,
+        address: Address,
+        fcb: bool,
+        buffer: &mut [u8],
+    ) -> Result<usize, CommandError> {
+        let user_data = self.user_data()?;
+        let frame = Frame::ControlFrame {
+            function: Function::SndUd { fcb },
+            address,
+            data: &user_data,
+        };
+        Ok(frame.encode_into(buffer)?)
+    }
+
+    /// Encodes the SND_UD frame into a newly allocated buffer
+    #[cfg(feature = "std")]
+    pub fn encode(&self, address: Address, fcb: bool) -> Result<Vec<u8>, CommandError> {
+        let mut buffer = vec![0; crate::frames::MAX_FRAME_LENGTH];
+        let length = self.encode_into(address, fcb, &mut buffer)?;
+        buffer.truncate(length);
+        Ok(buffer)
+    }
+}
+
+const fn baud_rate_control_information(baud_rate: u32) -> Result<u8, CommandError> {
+    match baud_rate {
+        300 => Ok(0xB8),
+        600 => Ok(0xB9),
+        1200 => Ok(0xBA),
+        2400 => Ok(0xBB),
+        4800 => Ok(0xBC),
+        9600 => Ok(0xBD),
+        19200 => Ok(0xBE),
+        38400 => Ok(0xBF),
+        _ => Err(CommandError::UnsupportedBaudRate { baud_rate }),
+    }
+}
+
+const fn to_bcd(mut number: u32) -> u32 {
+    let mut bcd = 0;
+    let mut shift = 0;
+    while number > 0 {
+        bcd |= (number % 10) << shift;
+        number /= 10;
+        shift += 4;
+    }
+    bcd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frames::MAX_FRAME_LENGTH;
+    use crate::user_data::data_information::{DataType, Month, SingleEveryOrInvalid};
+    use crate::user_data::{DataRecords, UserDataBlock};
+
+    fn encode(command: &Command, address: Address, buffer: &mut [u8]) -> usize {
+        command.encode_into(address, false, buffer).unwrap()
+    }
+
+    fn sent_user_data(frame: &[u8]) -> UserDataBlock<'_> {
+        let Ok(Frame::ControlFrame {
+            function: Function::SndUd { fcb: false },
+            data,
+            ..
+        }) = Frame::try_from(frame)
+        else {
+            panic!("expected a SND_UD control frame");
+        };
+        UserDataBlock::try_from(data).unwrap()
+    }
+
+    fn sent_value(frame: &[u8]) -> Option<DataType<'_>> {
+        let UserDataBlock::SendData {
+            variable_data_block,
+        } = sent_user_data(frame)
+        else {
+            panic!("expected send data");
+        };
+        DataRecords::from(variable_data_block)
+            .flatten()
+            .next()
+            .and_then(|record| record.data.value)
+    }
+
+    #[test]
+    fn test_set_primary_address() {
+        let mut buffer = [0u8; 32];
+        let length = encode(
+            &Command::SetPrimaryAddress(8),
+            Address::Broadcast {
+                reply_required: true,
+            },
+            &mut buffer,
+        );
+        assert_eq!(
+            &buffer[..length],
+            &[0x68, 0x06, 0x06, 0x68, 0x53, 0xFE, 0x51, 0x01, 0x7A, 0x08, 0x25, 0x16]
+        );
+        assert_eq!(sent_value(&buffer[..length]), Some(DataType::Number(8.0)));
+        assert_eq!(
+            Command::SetPrimaryAddress(251).user_data(),
+            Err(CommandError::InvalidPrimaryAddress { address: 251 })
+        );
+    }
+
+    #[test]
+    fn test_set_identification_number() {
+        let mut buffer = [0u8; 32];
+        let length = encode(
+            &Command::SetIdentificationNumber(12345678),
+            Address::Primary(1),
+            &mut buffer,
+        );
+        assert_eq!(&buffer[6..13], &[0x51, 0x0C, 0x79, 0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(
+            sent_value(&buffer[..length]),
+            Some(DataType::Number(12345678.0))
+        );
+        assert_eq!(
+            Command::SetIdentificationNumber(100_000_000).user_data(),
+            Err(CommandError::InvalidIdentificationNumber {
+                number: 100_000_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_date_time() {
+        let mut buffer = [0u8; 32];
+        let date_time = DateTime {
+            year: 24,
+            month: 10,
+            day: 18,
+            hour: 13,
+            minute: 45,
+        };
+        let length = encode(
+            &Command::SetDateTime(date_time),
+            Address::Primary(1),
+            &mut buffer,
+        );
+        assert_eq!(
+            sent_value(&buffer[..length]),
+            Some(DataType::DateTime(
+                SingleEveryOrInvalid::Single(18),
+                SingleEveryOrInvalid::Single(Month::October),
+                SingleEveryOrInvalid::Single(24),
+                SingleEveryOrInvalid::Single(13),
+                SingleEveryOrInvalid::Single(45),
+            ))
+        );
+        assert_eq!(
+            Command::SetDateTime(DateTime {
+                month: 13,
+                ..date_time
+            })
+            .user_data(),
+            Err(CommandError::InvalidDateTime)
+        );
+    }
+
+    #[test]
+    fn test_application_reset() {
+        let mut buffer = [0u8; 32];
+        let length = encode(
+            &Command::ApplicationReset(ApplicationResetSubcode::UserData(0x01)),
+            Address::Primary(1),
+            &mut buffer,
+        );
+        assert_eq!(
+            sent_user_data(&buffer[..length]),
+            UserDataBlock::ResetAtApplicationLevel {
+                subcode: ApplicationResetSubcode::UserData(0x01),
+                header: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_set_baud_rate() {
+        let mut buffer = [0u8; 32];
+        let length = encode(
+            &Command::SetBaudRate(2400),
+            Address::Primary(1),
+            &mut buffer,
+        );
+        assert_eq!(
+            &buffer[..length],
+            &[0x68, 0x03, 0x03, 0x68, 0x53, 0x01, 0xBB, 0x0F, 0x16]
+        );
+        assert_eq!(
+            sent_user_data(&buffer[..length]),
+            UserDataBlock::SetBaudRate { baud_rate: 2400 }
+        );
+        assert_eq!(
+            Command::SetBaudRate(115200).user_data(),
+            Err(CommandError::UnsupportedBaudRate { baud_rate: 115200 })
+        );
+    }
+
+    #[test]
+    fn test_select_secondary() {
+        let mut buffer = [0u8; 32];
+        let secondary_address = SecondaryAddress::new(0x1234_5678, 0x1EE6, 0x01, 0x07);
+        let length = encode(
+            &Command::SelectSecondary(secondary_address),
+            Address::Secondary,
+            &mut buffer,
+        );
+        assert_eq!(length, 17);
+        assert_eq!(
+            sent_user_data(&buffer[..length]),
+            UserDataBlock::SelectSlave { secondary_address }
+        );
+        assert_eq!(
+            Command::SelectSecondary(secondary_address).encode_into(
+                Address::Secondary,
+                false,
+                &mut buffer[..10]
+            ),
+            Err(CommandError::FrameError(FrameError::BufferTooSmall {
+                required: 17
+            }))
+        );
+    }
+
+    #[test]
+    fn test_select_for_readout() {
+        let mut buffer = [0u8; 64];
+        let length = encode(
+            &Command::SelectForReadout(ReadoutSelection::Global),
+            Address::Primary(1),
+            &mut buffer,
+        );
+        assert_eq!(&buffer[6..length - 2], &[0x51, 0x7F]);
+        let UserDataBlock::SendData {
+            variable_data_block,
+        } = sent_user_data(&buffer[..length])
+        else {
+            panic!("expected send data");
+        };
+        assert_eq!(
+            ReadoutSelection::try_from(variable_data_block),
+            Ok(ReadoutSelection::Global)
+        );
+
+        // volume of storage 1 and 12, maximum power of tariff 2 in subunit 1
+        let mut records = ArrayVec::new();
+        for storage_number in [1, 12] {
+            let mut volume = RecordSelection::new(&[0x13]).unwrap();
+            volume.storage_number = storage_number;
+            records.push(volume);
+        }
+        let mut power = RecordSelection::new(&[0x2B]).unwrap();
+        power.tariff = 2;
+        power.device = 1;
+        power.function_field = FunctionField::MaximumValue;
+        records.push(power);
+        let selection = ReadoutSelection::Records(records);
+
+        let length = encode(
+            &Command::SelectForReadout(selection.clone()),
+            Address::Primary(1),
+            &mut buffer,
+        );
+        assert_eq!(
+            &buffer[6..length - 2],
+            &[0x51, 0x48, 0x13, 0x88, 0x06, 0x13, 0x98, 0x60, 0x2B]
+        );
+        let UserDataBlock::SendData {
+            variable_data_block,
+        } = sent_user_data(&buffer[..length])
+        else {
+            panic!("expected send data");
+        };
+        assert_eq!(
+            ReadoutSelection::try_from(variable_data_block),
+            Ok(selection)
+        );
+
+        assert_eq!(
+            ReadoutSelection::try_from([0x04, 0x13, 0x00, 0x00, 0x00, 0x00].as_slice()),
+            Err(CommandError::InvalidSelection)
+        );
+        // CI field and 12 records of DIF, 10 DIFEs and 10 bytes VIB fill the whole frame
+        let mut record =
+            RecordSelection::new(&[0x93, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00])
+                .unwrap();
+        record.device = 1 << 9;
+        let mut records = ArrayVec::new();
+        for _ in 0..12 {
+            records.push(record.clone());
+        }
+        let mut buffer = [0u8; MAX_FRAME_LENGTH];
+        let length = encode(
+            &Command::SelectForReadout(ReadoutSelection::Records(records.clone())),
+            Address::Primary(1),
+            &mut buffer,
+        );
+        assert_eq!(length, MAX_FRAME_LENGTH);
+        let Ok(Frame::ControlFrame { data, .. }) = Frame::try_from(buffer.as_slice()) else {
+            panic!("expected a control frame");
+        };
+        assert_eq!(data.len(), MAX_COMMAND_LENGTH);
+        records.push(record);
+        assert_eq!(
+            Command::SelectForReadout(ReadoutSelection::Records(records)).user_data(),
+            Err(CommandError::CommandTooLong)
+        );
+
+        let mut out_of_range = RecordSelection::new(&[0x13]).unwrap();
+        out_of_range.device = 1 << 10;
+        let mut records = ArrayVec::new();
+        records.push(out_of_range);
+        assert_eq!(
+            Command::SelectForReadout(ReadoutSelection::Records(records)).user_data(),
+            Err(CommandError::SelectionOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Incremental decoding of frames from a byte stream such as a UART or TCP socket
+use super::{frame_candidate, is_start_byte, Candidate, Frame, FrameError, MAX_FRAME_LENGTH};
+use arrayvec::ArrayVec;
+
+/// Push-based decoder that buffers arbitrary chunks of bytes and emits complete frames.
+///
+/// Bytes that can not be the start of a frame are skipped, so the decoder resynchronizes
+/// on the next `0xE5`, `0x10` or `0x68`. A frame with a wrong checksum is reported as an
+/// error and dropped as a whole, decoding continues with the byte after it.
+///
+/// The buffer has a fixed capacity of `N` bytes, the default fits the longest frame.
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder<const N: usize = MAX_FRAME_LENGTH> {
+    buffer: ArrayVec<u8, N>,
+    consumed: usize,
+}
+
+impl<const N: usize> FrameDecoder<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            buffer: ArrayVec::new_const(),
+            consumed: 0,
+        }
+    }
+
+    /// Appends `data` to the internal buffer and returns the number of bytes accepted.
+    ///
+    /// Fewer bytes than offered are accepted when the buffer is full, the caller should
+    /// call `next_frame` until it returns `None` and push the remaining bytes again.
+    pub fn push(&mut self, data: &[u8]) -> usize {
+        self.discard_consumed();
+        let count = data.len().min(self.buffer.remaining_capacity());
+        if let Some(accepted) = data.get(..count) {
+            // cannot fail, the length was limited to the remaining capacity
+            let _ = self.buffer.try_extend_from_slice(accepted);
+        }
+        count
+    }
+
+    /// Number of buffered bytes that have not been decoded yet.
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.consumed
+    }
+
+    /// Drops all buffered bytes.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.consumed = 0;
+    }
+
+    /// Returns the next complete frame in the buffer, or `None` if more bytes are needed.
+    pub fn next_frame(&mut self) -> Option<Result<Frame<'_>, FrameError>> {
+        self.discard_consumed();
+        loop {
+            match self.buffer.iter().position(|byte| is_start_byte(*byte)) {
+                Some(start) => self.discard(start),
+                None => {
+                    self.buffer.clear();
+                    return None;
+                }
+            }
+
+            let length = match frame_candidate(&self.buffer) {
+                Candidate::Incomplete { required } if required > N => {
+                    self.discard(1);
+                    return Some(Err(FrameError::BufferTooSmall { required }));
+                }
+                Candidate::Incomplete { .. } => return None,
+                Candidate::Noise(_) => {
+                    self.discard(1);
+                    continue;
+                }
+                Candidate::Complete(length) => length,
+            };
+
+            self.consumed = length;
+            let frame = self.buffer.get(..length)?;
+            return Some(Frame::try_from(frame));
+        }
+    }
+
+    fn discard(&mut self, count: usize) {
+        self.buffer.drain(..count.min(self.buffer.len()));
+    }
+
+    fn discard_consumed(&mut self) {
+        self.discard(self.consumed);
+        self.consumed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frames::{Address, Function};
+
+    const LONG_FRAME: [u8; 12] = [
+        0x68, 0x06, 0x06, 0x68, 0x53, 0xFE, 0x51, 0x01, 0x7A, 0x08, 0x25, 0x16,
+    ];
+    const SHORT_FRAME: [u8; 5] = [0x10, 0x7B, 0x8b, 0x06, 0x16];
+
+    #[test]
+    fn test_decode_in_chunks() {
+        let mut decoder: FrameDecoder = FrameDecoder::new();
+        for chunk in LONG_FRAME.chunks(5) {
+            assert_eq!(decoder.next_frame(), None);
+            assert_eq!(decoder.push(chunk), chunk.len());
+        }
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Ok(Frame::ControlFrame {
+                function: Function::SndUd { fcb: false },
+                address: Address::Broadcast {
+                    reply_required: true
+                },
+                data: &[0x51, 0x01, 0x7A, 0x08],
+            }))
+        );
+        assert_eq!(decoder.next_frame(), None);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn test_skips_noise_between_frames() {
+        let mut decoder: FrameDecoder = FrameDecoder::new();
+        decoder.push(&[0x00, 0xFF, 0x68, 0x01]);
+        decoder.push(&SHORT_FRAME);
+        decoder.push(&[0x42, 0xE5, 0x16]);
+        decoder.push(&LONG_FRAME);
+
+        assert!(matches!(
+            decoder.next_frame(),
+            Some(Ok(Frame::ShortFrame { .. }))
+        ));
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Ok(Frame::SingleCharacter { character: 0xE5 }))
+        );
+        assert!(matches!(
+            decoder.next_frame(),
+            Some(Ok(Frame::ControlFrame { .. }))
+        ));
+        assert_eq!(decoder.next_frame(), None);
+    }
+
+    #[test]
+    fn test_reports_checksum_error_and_keeps_sync() {
+        let mut corrupted = LONG_FRAME;
+        corrupted[8] = 0x7B;
+
+        let mut decoder: FrameDecoder = FrameDecoder::new();
+        decoder.push(&corrupted);
+        decoder.push(&SHORT_FRAME);
+
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::WrongChecksum {
+                expected: 0x25,
+                actual: 0x26
+            }))
+        );
+        assert!(matches!(
+            decoder.next_frame(),
+            Some(Ok(Frame::ShortFrame { .. }))
+        ));
+        assert_eq!(decoder.next_frame(), None);
+    }
+
+    #[test]
+    fn test_small_buffer() {
+        let mut decoder = FrameDecoder::<8>::new();
+        assert_eq!(decoder.push(&LONG_FRAME), 8);
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::BufferTooSmall { required: 12 }))
+        );
+        assert_eq!(decoder.next_frame(), None);
+        assert_eq!(decoder.push(&SHORT_FRAME), 5);
+        assert!(matches!(
+            decoder.next_frame(),
+            Some(Ok(Frame::ShortFrame { .. }))
+        ));
+    }
+}
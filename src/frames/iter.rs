@@ -0,0 +1,121 @@
+//! Iteration over many frames that were captured back to back into one buffer
+use super::{frame_candidate, is_start_byte, Candidate, Frame, FrameError};
+
+/// Zero-copy iterator over concatenated frames, for example a raw bus capture.
+///
+/// Each item is the offset of the frame in the buffer together with the parse result.
+/// Bytes that do not form a frame are reported once as an error and skipped up to the
+/// next possible start byte, a truncated frame at the end of the buffer ends iteration.
+#[derive(Debug, Clone)]
+pub struct FrameIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FrameIter<'a> {
+    #[must_use]
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+}
+
+impl<'a> From<&'a [u8]> for FrameIter<'a> {
+    fn from(data: &'a [u8]) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<'a> Iterator for FrameIter<'a> {
+    type Item = (usize, Result<Frame<'a>, FrameError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.data.get(self.offset..).filter(|x| !x.is_empty())?;
+        let offset = self.offset;
+
+        match frame_candidate(remaining) {
+            Candidate::Complete(length) => {
+                self.offset += length;
+                Some((offset, Frame::try_from(remaining.get(..length)?)))
+            }
+            Candidate::Incomplete { .. } => {
+                self.offset = self.data.len();
+                Some((offset, Err(FrameError::LengthShort)))
+            }
+            Candidate::Noise(error) => {
+                self.offset += remaining
+                    .iter()
+                    .skip(1)
+                    .position(|byte| is_start_byte(*byte))
+                    .map_or(remaining.len(), |position| position + 1);
+                Some((offset, Err(error)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterate_concatenated_frames() {
+        let capture: &[u8] = &[
+            0x10, 0x5B, 0x01, 0x5C, 0x16, // REQ_UD2 to address 1
+            0xE5, // acknowledgement
+            0x68, 0x03, 0x03, 0x68, 0x53, 0x01, 0x51, 0xA5, 0x16, // SND_UD
+            0xE5,
+        ];
+
+        let mut iter = FrameIter::new(capture).map(|(offset, frame)| (offset, frame.is_ok()));
+        assert_eq!(iter.next(), Some((0, true)));
+        assert_eq!(iter.next(), Some((5, true)));
+        assert_eq!(iter.next(), Some((6, true)));
+        assert_eq!(iter.next(), Some((15, true)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_skips_garbage_and_wrong_checksum() {
+        let capture: &[u8] = &[
+            0x00, 0x01, 0x02, // garbage
+            0x10, 0x5B, 0x01, 0x5D, 0x16, // wrong checksum
+            0xE5, 0x68, 0x03, // truncated
+        ];
+
+        let mut iter = FrameIter::new(capture);
+        assert_eq!(iter.next(), Some((0, Err(FrameError::InvalidStartByte))));
+        assert_eq!(
+            iter.next(),
+            Some((
+                3,
+                Err(FrameError::WrongChecksum {
+                    expected: 0x5D,
+                    actual: 0x5C
+                })
+            ))
+        );
+        assert_eq!(
+            iter.next(),
+            Some((8, Ok(Frame::SingleCharacter { character: 0xE5 })))
+        );
+        assert_eq!(iter.next(), Some((9, Err(FrameError::LengthShort))));
+        assert_eq!(iter.next(), None);
+    }
+    #[test]
+    fn test_mbus_data_for_every_frame() {
+        let capture: &[u8] = &[
+            0x68, 0x3C, 0x3C, 0x68, 0x08, 0x08, 0x72, 0x78, 0x03, 0x49, 0x11, 0x77, 0x04, 0x0E,
+            0x16, 0x0A, 0x00, 0x00, 0x00, 0x0C, 0x78, 0x78, 0x03, 0x49, 0x11, 0x04, 0x13, 0x31,
+            0xD4, 0x00, 0x00, 0x42, 0x6C, 0x00, 0x00, 0x44, 0x13, 0x00, 0x00, 0x00, 0x00, 0x04,
+            0x6D, 0x0B, 0x0B, 0xCD, 0x13, 0x02, 0x27, 0x00, 0x00, 0x09, 0xFD, 0x0E, 0x02, 0x09,
+            0xFD, 0x0F, 0x06, 0x0F, 0x00, 0x01, 0x75, 0x13, 0xD3, 0x16, 0xE5,
+        ];
+
+        let mut telegrams = FrameIter::new(capture)
+            .filter_map(|(_, frame)| frame.ok())
+            .map(crate::MbusData::from);
+        assert!(telegrams.next().is_some_and(|x| x.user_data.is_some()));
+        assert!(telegrams.next().is_some_and(|x| x.user_data.is_none()));
+        assert!(telegrams.next().is_none());
+    }
+}
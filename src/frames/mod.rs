@@ -1,5 +1,11 @@
 //! is part of the MBUS data link layer
 //! It is used to encapsulate the application layer data
+mod decoder;
+mod iter;
+
+pub use decoder::FrameDecoder;
+pub use iter::FrameIter;
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -81,6 +87,18 @@ impl TryFrom<u8> for Function {
         }
     }
 }
+impl From<&Function> for u8 {
+    fn from(function: &Function) -> Self {
+        match function {
+            Function::SndNk => 0x40,
+            Function::SndUd { fcb } => 0x53 | (u8::from(*fcb) << 5),
+            Function::ReqUd2 { fcb } => 0x5B | (u8::from(*fcb) << 5),
+            Function::ReqUd1 { fcb } => 0x5A | (u8::from(*fcb) << 5),
+            Function::RspUd { acd, dfc } => 0x08 | (u8::from(*acd) << 5) | (u8::from(*dfc) << 4),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -106,7 +124,7 @@ impl std::fmt::Display for Address {
 }
 
 impl Address {
-    const fn from(byte: u8) -> Self {
+    pub(crate) const fn from(byte: u8) -> Self {
         match byte {
             0 => Self::Uninitalized,
             253 => Self::Secondary,
@@ -121,6 +139,22 @@ impl Address {
     }
 }
 
+impl From<&Address> for u8 {
+    fn from(address: &Address) -> Self {
+        match address {
+            Address::Uninitalized => 0,
+            Address::Primary(byte) => *byte,
+            Address::Secondary => 253,
+            Address::Broadcast {
+                reply_required: true,
+            } => 254,
+            Address::Broadcast {
+                reply_required: false,
+            } => 255,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -134,6 +168,8 @@ pub enum FrameError {
     WrongChecksum { expected: u8, actual: u8 },
     InvalidControlInformation { byte: u8 },
     InvalidFunction { byte: u8 },
+    DataTooLong { length: usize },
+    BufferTooSmall { required: usize },
 }
 
 impl<'a> TryFrom<&'a [u8]> for Frame<'a> {
@@ -164,16 +200,19 @@ impl<'a> TryFrom<&'a [u8]> for Frame<'a> {
                 }
                 let control_field = *data.get(4).ok_or(FrameError::LengthShort)?;
                 let address_field = *data.get(5).ok_or(FrameError::LengthShort)?;
-                match control_field {
-                    0x53 => Ok(Frame::ControlFrame {
-                        function: Function::try_from(control_field)?,
-                        address: Address::from(address_field),
-                        data: data.get(6..data.len() - 2).ok_or(FrameError::LengthShort)?,
+                let function = Function::try_from(control_field)?;
+                let address = Address::from(address_field);
+                let data = data.get(6..data.len() - 2).ok_or(FrameError::LengthShort)?;
+                match function {
+                    Function::SndUd { .. } => Ok(Frame::ControlFrame {
+                        function,
+                        address,
+                        data,
                     }),
                     _ => Ok(Frame::LongFrame {
-                        function: Function::try_from(control_field)?,
-                        address: Address::from(address_field),
-                        data: data.get(6..data.len() - 2).ok_or(FrameError::LengthShort)?,
+                        function,
+                        address,
+                        data,
                     }),
                 }
             }
@@ -193,6 +232,141 @@ impl<'a> TryFrom<&'a [u8]> for Frame<'a> {
     }
 }
 
+/// The largest user data a long or control frame can carry, the L-field
+/// counts the C- and A-field as well and is a single byte.
+pub const MAX_FRAME_DATA_LENGTH: usize = 253;
+
+/// The length of the largest long frame including start, length, checksum and stop bytes.
+pub const MAX_FRAME_LENGTH: usize = MAX_FRAME_DATA_LENGTH + 8;
+
+impl Frame<'_> {
+    /// Number of bytes the frame occupies on the wire.
+    #[must_use]
+    pub const fn encoded_len(&self) -> usize {
+        match self {
+            Frame::SingleCharacter { .. } => 1,
+            Frame::ShortFrame { .. } => 5,
+            Frame::LongFrame { data, .. } | Frame::ControlFrame { data, .. } => data.len() + 8,
+        }
+    }
+
+    /// Writes the frame in its wire format into `buffer` and returns the number
+    /// of bytes written. This is the inverse of `Frame::try_from`.
+    pub fn encode_into(&self, buffer: &mut [u8]) -> Result<usize, FrameError> {
+        let required = self.encoded_len();
+        let output = buffer
+            .get_mut(..required)
+            .ok_or(FrameError::BufferTooSmall { required })?;
+
+        match self {
+            Frame::SingleCharacter { character } => {
+                output.copy_from_slice(&[*character]);
+            }
+            Frame::ShortFrame { function, address } => {
+                let control_field = u8::from(function);
+                let address_field = u8::from(address);
+                output.copy_from_slice(&[
+                    0x10,
+                    control_field,
+                    address_field,
+                    control_field.wrapping_add(address_field),
+                    0x16,
+                ]);
+            }
+            Frame::LongFrame {
+                function,
+                address,
+                data,
+            }
+            | Frame::ControlFrame {
+                function,
+                address,
+                data,
+            } => {
+                if data.len() > MAX_FRAME_DATA_LENGTH {
+                    return Err(FrameError::DataTooLong { length: data.len() });
+                }
+                let length = (data.len() + 2) as u8;
+                let control_field = u8::from(function);
+                let address_field = u8::from(address);
+                let checksum = calculate_checksum(data)
+                    .wrapping_add(control_field)
+                    .wrapping_add(address_field);
+
+                let (header, rest) = output.split_at_mut(6);
+                header.copy_from_slice(&[0x68, length, length, 0x68, control_field, address_field]);
+                let (payload, trailer) = rest.split_at_mut(data.len());
+                payload.copy_from_slice(data);
+                trailer.copy_from_slice(&[checksum, 0x16]);
+            }
+        }
+
+        Ok(required)
+    }
+
+    /// Encodes the frame into a newly allocated buffer.
+    #[cfg(feature = "std")]
+    pub fn encode(&self) -> Result<Vec<u8>, FrameError> {
+        let mut buffer = vec![0; self.encoded_len()];
+        self.encode_into(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+const SINGLE_CHARACTER: u8 = 0xE5;
+const SHORT_FRAME_START: u8 = 0x10;
+const LONG_FRAME_START: u8 = 0x68;
+const STOP_BYTE: u8 = 0x16;
+
+const fn is_start_byte(byte: u8) -> bool {
+    matches!(
+        byte,
+        SINGLE_CHARACTER | SHORT_FRAME_START | LONG_FRAME_START
+    )
+}
+
+/// Result of looking at the bytes that might start a frame, before validating it.
+enum Candidate {
+    /// More bytes are needed to tell where the frame ends.
+    Incomplete { required: usize },
+    /// The bytes can not be the start of a frame.
+    Noise(FrameError),
+    /// A frame of the given length with correct framing bytes.
+    Complete(usize),
+}
+
+/// Determines the length of the frame at the start of `data` using the fixed length of
+/// single character and short frames and the L-field of long and control frames.
+fn frame_candidate(data: &[u8]) -> Candidate {
+    let header = |index: usize| data.get(index).copied();
+
+    let length = match header(0) {
+        None => return Candidate::Incomplete { required: 1 },
+        Some(SINGLE_CHARACTER) => return Candidate::Complete(1),
+        Some(SHORT_FRAME_START) => 5,
+        Some(LONG_FRAME_START) => {
+            let (Some(first), Some(second), Some(start)) = (header(1), header(2), header(3)) else {
+                return Candidate::Incomplete { required: 4 };
+            };
+            if first != second || start != LONG_FRAME_START || first < 2 {
+                return Candidate::Noise(FrameError::WrongLengthIndication);
+            }
+            usize::from(first) + 6
+        }
+        Some(_) => return Candidate::Noise(FrameError::InvalidStartByte),
+    };
+
+    match header(length - 1) {
+        None => Candidate::Incomplete { required: length },
+        Some(STOP_BYTE) => Candidate::Complete(length),
+        Some(_) => Candidate::Noise(FrameError::InvalidStopByte),
+    }
+}
+
+fn calculate_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |acc: u8, &x| acc.wrapping_add(x))
+}
+
 fn validate_checksum(data: &[u8]) -> Result<(), FrameError> {
     // Assuming the checksum is the second to last byte in the data array.
     let checksum_byte_index = data.len() - 2;
@@ -200,11 +374,10 @@ fn validate_checksum(data: &[u8]) -> Result<(), FrameError> {
         .get(checksum_byte_index)
         .ok_or(FrameError::LengthShort)?;
 
-    let calculated_checksum = data
-        .get(..checksum_byte_index)
-        .ok_or(FrameError::LengthShort)?
-        .iter()
-        .fold(0, |acc: u8, &x| acc.wrapping_add(x));
+    let calculated_checksum = calculate_checksum(
+        data.get(..checksum_byte_index)
+            .ok_or(FrameError::LengthShort)?,
+    );
 
     if checksum_byte == calculated_checksum {
         Ok(())
@@ -240,6 +413,12 @@ impl std::fmt::Display for FrameError {
             }
             FrameError::InvalidFunction { byte } => write!(f, "Invalid function: {}", byte),
             FrameError::WrongLengthIndication => write!(f, "Wrong length indication"),
+            FrameError::DataTooLong { length } => {
+                write!(f, "Data too long for a single frame: {}", length)
+            }
+            FrameError::BufferTooSmall { required } => {
+                write!(f, "Buffer too small, required: {}", required)
+            }
         }
     }
 }
@@ -282,6 +461,15 @@ mod tests {
                 data: &[0x51]
             })
         );
+        // SND_UD with the frame count bit set is a control frame as well
+        assert_eq!(
+            Frame::try_from([0x68, 0x03, 0x03, 0x68, 0x73, 0x01, 0x51, 0xC5, 0x16].as_slice()),
+            Ok(Frame::ControlFrame {
+                function: Function::SndUd { fcb: true },
+                address: Address::from(0x01),
+                data: &[0x51]
+            })
+        );
 
         assert_eq!(
             Frame::try_from(example),
@@ -297,4 +485,71 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let frames: [&[u8]; 5] = [
+            &[0xE5],
+            &[0x10, 0x7B, 0x8b, 0x06, 0x16],
+            &[0x10, 0x40, 0xFE, 0x3E, 0x16],
+            &[0x68, 0x03, 0x03, 0x68, 0x53, 0x01, 0x51, 0xA5, 0x16],
+            &[
+                0x68, 0x06, 0x06, 0x68, 0x53, 0xFE, 0x51, 0x01, 0x7A, 0x08, 0x25, 0x16,
+            ],
+        ];
+
+        for bytes in frames {
+            let frame = Frame::try_from(bytes).unwrap();
+            let mut buffer = [0u8; 16];
+            let length = frame.encode_into(&mut buffer).unwrap();
+            assert_eq!(&buffer[..length], bytes);
+            assert_eq!(length, frame.encoded_len());
+        }
+    }
+
+    #[test]
+    fn test_encode_long_frame() {
+        let frame = Frame::LongFrame {
+            function: Function::RspUd {
+                acd: false,
+                dfc: false,
+            },
+            address: Address::Primary(1),
+            data: &[0x72, 0x01, 0x02],
+        };
+        let mut buffer = [0u8; 11];
+        assert_eq!(frame.encode_into(&mut buffer), Ok(11));
+        assert_eq!(
+            buffer,
+            [0x68, 0x05, 0x05, 0x68, 0x08, 0x01, 0x72, 0x01, 0x02, 0x7E, 0x16]
+        );
+        assert_eq!(Frame::try_from(buffer.as_slice()), Ok(frame));
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let frame = Frame::ShortFrame {
+            function: Function::ReqUd2 { fcb: true },
+            address: Address::Primary(5),
+        };
+        let mut buffer = [0u8; 4];
+        assert_eq!(
+            frame.encode_into(&mut buffer),
+            Err(FrameError::BufferTooSmall { required: 5 })
+        );
+
+        let data = [0u8; MAX_FRAME_DATA_LENGTH + 1];
+        let frame = Frame::LongFrame {
+            function: Function::SndUd { fcb: false },
+            address: Address::Primary(5),
+            data: &data,
+        };
+        let mut buffer = [0u8; MAX_FRAME_DATA_LENGTH + 9];
+        assert_eq!(
+            frame.encode_into(&mut buffer),
+            Err(FrameError::DataTooLong {
+                length: MAX_FRAME_DATA_LENGTH + 1
+            })
+        );
+    }
 }
@@ -52,8 +52,17 @@ use prettytable::{format, row, Table};
 #[cfg(feature = "std")]
 use std::str;
 
+pub mod afl;
+pub mod commands;
 pub mod frames;
+pub mod master;
+pub mod readout;
+pub mod search;
+#[cfg(feature = "decryption")]
+pub mod security;
+pub mod slave;
 pub mod user_data;
+pub mod wmbus;
 
 #[derive(Debug)]
 #[cfg_attr(
@@ -92,32 +101,46 @@ impl<'a> TryFrom<&'a [u8]> for MbusData<'a> {
     type Error = MbusError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let frame = frames::Frame::try_from(data)?;
-        let mut user_data = None;
-        let mut data_records = None;
-        match &frame {
-            frames::Frame::LongFrame { data, .. } => {
-                if let Ok(x) = user_data::UserDataBlock::try_from(*data) {
-                    user_data = Some(x);
-                    if let Ok(user_data::UserDataBlock::VariableDataStructure {
-                        fixed_data_header: _,
-                        variable_data_block,
-                    }) = user_data::UserDataBlock::try_from(*data)
-                    {
-                        data_records = Some(variable_data_block.into());
-                    }
-                }
+        Ok(Self::from(frames::Frame::try_from(data)?))
+    }
+}
+
+impl MbusData<'_> {
+    /// Returns true if the slave signalled with DIF 0x1F that the readout continues in the next telegram
+    #[must_use]
+    pub fn more_records_follow(&self) -> bool {
+        self.data_records
+            .as_ref()
+            .is_some_and(user_data::DataRecords::more_records_follow)
+    }
+}
+
+/// Decodes the application layer of an already parsed frame, e.g. one yielded by `frames::FrameIter`.
+impl<'a> From<frames::Frame<'a>> for MbusData<'a> {
+    fn from(frame: frames::Frame<'a>) -> Self {
+        let user_data = match &frame {
+            frames::Frame::LongFrame { data, .. } | frames::Frame::ControlFrame { data, .. } => {
+                user_data::UserDataBlock::try_from(*data).ok()
             }
-            frames::Frame::SingleCharacter { .. } => (),
-            frames::Frame::ShortFrame { .. } => (),
-            frames::Frame::ControlFrame { .. } => (),
+            frames::Frame::SingleCharacter { .. } => None,
+            frames::Frame::ShortFrame { .. } => None,
         };
+        let data_records = user_data.as_ref().and_then(|user_data| {
+            // encrypted records have to be decrypted first, see the `decryption` feature
+            if user_data
+                .short_header()
+                .is_some_and(|header| header.configuration_field.is_encrypted())
+            {
+                return None;
+            }
+            user_data.variable_data_block().map(Into::into)
+        });
 
-        Ok(MbusData {
+        MbusData {
             frame,
             user_data,
             data_records,
-        })
+        }
     }
 }
 
@@ -167,6 +190,7 @@ fn parse_to_yaml(input: &str) -> std::string::String {
 
 #[cfg(feature = "std")]
 fn parse_to_table(input: &str) -> std::string::String {
+    use user_data::manufacturer_specific::{ManufacturerVifs, BUILT_IN_VIFS};
     use user_data::UserDataBlock;
 
     let data = clean_and_convert(input);
@@ -176,13 +200,23 @@ fn parse_to_table(input: &str) -> std::string::String {
         let mut table = Table::new();
         table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
 
+        let is_control_frame = matches!(parsed_data.frame, frames::Frame::ControlFrame { .. });
         match parsed_data.frame {
             frames::Frame::LongFrame {
                 function,
                 address,
                 data: _,
+            }
+            | frames::Frame::ControlFrame {
+                function,
+                address,
+                data: _,
             } => {
-                table_output.push_str("Long Frame \n");
+                if is_control_frame {
+                    table_output.push_str("Control Frame \n");
+                } else {
+                    table_output.push_str("Long Frame \n");
+                }
 
                 table.set_titles(row!["Function", "Address"]);
                 table.add_row(row![function, address]);
@@ -190,7 +224,13 @@ fn parse_to_table(input: &str) -> std::string::String {
                 table_output.push_str(&table.to_string());
                 table = Table::new();
 
-                match parsed_data.user_data {
+                let encrypted = parsed_data
+                    .user_data
+                    .as_ref()
+                    .and_then(UserDataBlock::short_header)
+                    .is_some_and(|header| header.configuration_field.is_encrypted());
+
+                match &parsed_data.user_data {
                     Some(UserDataBlock::VariableDataStructure {
                         fixed_data_header,
                         variable_data_block: _,
@@ -199,11 +239,12 @@ fn parse_to_table(input: &str) -> std::string::String {
                             fixed_data_header.identification_number,
                             fixed_data_header
                                 .manufacturer
+                                .as_ref()
                                 .map(|i| i.to_string())
                                 .unwrap_or_else(|_| "invalid".to_string()),
                             fixed_data_header.access_number,
                             fixed_data_header.status,
-                            fixed_data_header.signature,
+                            fixed_data_header.configuration_field,
                             fixed_data_header.version,
                             fixed_data_header.medium,
                         ]);
@@ -213,7 +254,7 @@ fn parse_to_table(input: &str) -> std::string::String {
                             "Manufacturer",
                             "Access Number",
                             "Status",
-                            "Signature",
+                            "Configuration Field",
                             "Version",
                             "Medium",
                         ]);
@@ -243,10 +284,84 @@ fn parse_to_table(input: &str) -> std::string::String {
                             counter2,
                         ]);
                     }
-                    Some(UserDataBlock::ResetAtApplicationLevel { subcode }) => {
+                    Some(UserDataBlock::ResetAtApplicationLevel { subcode, .. }) => {
                         table.set_titles(row!["Function", "Address", "Subcode"]);
                         table.add_row(row![function, address, subcode]);
                     }
+                    Some(UserDataBlock::VariableDataStructureWithShortHeader {
+                        short_header,
+                        variable_data_block: _,
+                    }) => {
+                        table.set_titles(row!["Access Number", "Status", "Configuration Field",]);
+                        table.add_row(row![
+                            short_header.access_number,
+                            short_header.status,
+                            short_header.configuration_field,
+                        ]);
+                    }
+                    Some(UserDataBlock::VariableDataStructureWithoutHeader { .. }) => {
+                        table.set_titles(row!["Function", "Address", "Control Information"]);
+                        table.add_row(row![function, address, "Response without header"]);
+                    }
+                    Some(UserDataBlock::SendDataWithHeader { header, .. })
+                    | Some(UserDataBlock::TransportLayer { header }) => {
+                        let header = header.short_header();
+                        table.set_titles(row![
+                            "Function",
+                            "Address",
+                            "Access Number",
+                            "Status",
+                            "Configuration Field",
+                        ]);
+                        table.add_row(row![
+                            function,
+                            address,
+                            header.access_number,
+                            header.status,
+                            header.configuration_field,
+                        ]);
+                    }
+                    Some(UserDataBlock::SetBaudRate { baud_rate }) => {
+                        table.set_titles(row!["Function", "Address", "Baud Rate"]);
+                        table.add_row(row![function, address, baud_rate]);
+                    }
+                    Some(UserDataBlock::ApplicationError { error, .. }) => {
+                        table.set_titles(row!["Function", "Address", "Application Error"]);
+                        table.add_row(row![function, address, error]);
+                    }
+                    Some(UserDataBlock::Alarm { alarm_status, .. }) => {
+                        table.set_titles(row!["Function", "Address", "Alarm Status"]);
+                        table.add_row(row![function, address, format!("{:#010b}", alarm_status)]);
+                    }
+                    Some(UserDataBlock::FormatFrame {
+                        format_signature,
+                        headers,
+                    }) => {
+                        table.set_titles(row!["Format Signature", "Headers"]);
+                        table.add_row(row![
+                            format!("{:#06X}", format_signature),
+                            format!("{:02X?}", headers)
+                        ]);
+                    }
+                    Some(UserDataBlock::CompactFrame {
+                        format_signature,
+                        data_crc,
+                        data: _,
+                    }) => {
+                        table.set_titles(row!["Format Signature", "Data CRC"]);
+                        table.add_row(row![
+                            format!("{:#06X}", format_signature),
+                            format!("{:#06X}", data_crc)
+                        ]);
+                    }
+                    Some(UserDataBlock::SendData { .. }) => {
+                        table.set_titles(row!["Function", "Address", "Control Information"]);
+                        table.add_row(row![function, address, "Send Data"]);
+                    }
+                    Some(UserDataBlock::SelectSlave { secondary_address }) => {
+                        table.set_titles(row!["Function", "Address", "Secondary Address"]);
+                        table.add_row(row![function, address, secondary_address]);
+                    }
                     None => {
                         table.set_titles(row!["Function", "Address"]);
                         table.add_row(row![function, address]);
@@ -256,17 +371,39 @@ fn parse_to_table(input: &str) -> std::string::String {
                 table_output.push_str(&table.to_string());
                 table = Table::new();
 
+                if encrypted {
+                    table_output.push_str("Encrypted payload, records not decoded\n");
+                }
+
                 table.set_titles(row!["Value", "Data Information",]);
 
+                let fixed_data_header = match &parsed_data.user_data {
+                    Some(UserDataBlock::VariableDataStructure {
+                        fixed_data_header, ..
+                    }) => Some(fixed_data_header),
+                    _ => None,
+                };
+                let mut manufacturer_vifs = ManufacturerVifs::<{ BUILT_IN_VIFS.len() }>::new();
+                if let Err(error) = manufacturer_vifs.register_built_in() {
+                    table_output.push_str(&format!("{}\n", error));
+                }
+
                 if let Some(data_records) = parsed_data.data_records {
                     for record in data_records.flatten() {
-                        let value_information = match record
-                            .data_record_header
-                            .processed_data_record_header
-                            .value_information
-                        {
-                            Some(x) => format!("{}", x),
-                            None => "None".to_string(),
+                        let manufacturer_vif = fixed_data_header
+                            .and_then(|header| manufacturer_vifs.find_for_record(header, &record));
+                        let value_information = match (
+                            manufacturer_vif,
+                            record
+                                .data_record_header
+                                .processed_data_record_header
+                                .value_information,
+                        ) {
+                            (Some(entry), _) => {
+                                format!("{}({})", entry.value_information(), entry.label)
+                            }
+                            (None, Some(x)) => format!("{}", x),
+                            (None, None) => "None".to_string(),
                         };
 
                         let data_information = match record
@@ -291,9 +428,6 @@ fn parse_to_table(input: &str) -> std::string::String {
             frames::Frame::SingleCharacter { .. } => {
                 table_output.push_str("Single Character Frame\n");
             }
-            frames::Frame::ControlFrame { .. } => {
-                table_output.push_str("Control Frame\n");
-            }
         }
 
         table_output.push_str(&table.to_string());
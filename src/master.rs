@@ -0,0 +1,511 @@
+//! Sans-IO state machine for the master side of the wired M-Bus link layer (EN 13757-2)
+//!
+//! The master decides which frame to send next, keeps track of the frame count bit of the
+//! slave and validates the responses. Sending the frames and measuring the timeout is left
+//! to the caller, which makes it usable with any serial port or test double.
+use arrayvec::ArrayVec;
+
+use crate::frames::{Address, Frame, FrameError, Function, MAX_FRAME_DATA_LENGTH};
+
+const ACKNOWLEDGE: u8 = 0xE5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MasterConfig {
+    pub baud_rate: u32,
+    /// How often a request is repeated after a timeout or a corrupted response
+    pub retries: u8,
+}
+
+impl Default for MasterConfig {
+    fn default() -> Self {
+        Self {
+            baud_rate: 2400,
+            retries: 2,
+        }
+    }
+}
+
+impl MasterConfig {
+    /// A slave has to answer within 330 bit periods plus 50 ms
+    #[must_use]
+    pub const fn response_timeout_ms(&self) -> u32 {
+        if self.baud_rate == 0 {
+            return 50;
+        }
+        (330 * 1000u32).div_ceil(self.baud_rate) + 50
+    }
+}
+
+/// What the bus returned after a request was sent
+#[derive(Debug, PartialEq)]
+pub enum Response<'a> {
+    /// Nothing was received within [`MasterConfig::response_timeout_ms`]
+    Timeout,
+    Invalid(FrameError),
+    Frame(Frame<'a>),
+}
+
+impl<'a> From<Result<Frame<'a>, FrameError>> for Response<'a> {
+    fn from(result: Result<Frame<'a>, FrameError>) -> Self {
+        match result {
+            Ok(frame) => Self::Frame(frame),
+            Err(error) => Self::Invalid(error),
+        }
+    }
+}
+
+/// Result of a handled response
+#[derive(Debug, PartialEq)]
+pub enum Outcome<'a> {
+    /// Send [`Master::request`] again, the last response was missing or corrupted
+    Retry,
+    /// The link was reset with SND_NKE, send [`Master::request`] to continue
+    Initialized,
+    /// The slave acknowledged the user data
+    Acknowledged,
+    /// The slave answered REQ_UD2 with RSP_UD
+    UserData {
+        address: Address,
+        access_demand: bool,
+        data_flow_control: bool,
+        data: &'a [u8],
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MasterError {
+    /// A request is still waiting for its response
+    Busy,
+    /// There is no request waiting for a response
+    Idle,
+    /// A broadcast without reply can not be used to read data
+    NoReplyExpected,
+    DataTooLong {
+        length: usize,
+    },
+    /// Still no valid response after all retries
+    NoResponse {
+        attempts: u8,
+    },
+    AddressMismatch {
+        expected: u8,
+        received: u8,
+    },
+    /// The response does not fit the request, e.g. a frame sent by another master
+    UnexpectedResponse,
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for MasterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MasterError::Busy => write!(f, "Request still pending"),
+            MasterError::Idle => write!(f, "No request pending"),
+            MasterError::NoReplyExpected => write!(f, "Broadcast without reply"),
+            MasterError::DataTooLong { length } => write!(f, "Data too long: {}", length),
+            MasterError::NoResponse { attempts } => {
+                write!(f, "No response after {} attempts", attempts)
+            }
+            MasterError::AddressMismatch { expected, received } => {
+                write!(
+                    f,
+                    "Address mismatch: expected {}, received {}",
+                    expected, received
+                )
+            }
+            MasterError::UnexpectedResponse => write!(f, "Unexpected response"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MasterError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Request {
+    ReadUserData,
+    SendUserData,
+}
+
+/// Link layer state of the master towards a single slave address
+#[derive(Debug, Clone)]
+pub struct Master {
+    address: Address,
+    config: MasterConfig,
+    initialized: bool,
+    fcb: bool,
+    attempts: u8,
+    pending: Option<Request>,
+    payload: ArrayVec<u8, MAX_FRAME_DATA_LENGTH>,
+}
+
+impl Master {
+    #[must_use]
+    pub fn new(address: Address) -> Self {
+        Self::with_config(address, MasterConfig::default())
+    }
+
+    #[must_use]
+    pub fn with_config(address: Address, config: MasterConfig) -> Self {
+        Self {
+            address,
+            config,
+            initialized: false,
+            fcb: false,
+            attempts: 0,
+            pending: None,
+            payload: ArrayVec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn address(&self) -> &Address {
+        &self.address
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &MasterConfig {
+        &self.config
+    }
+
+    #[must_use]
+    pub const fn response_timeout_ms(&self) -> u32 {
+        self.config.response_timeout_ms()
+    }
+
+    #[must_use]
+    pub const fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Forces a SND_NKE before the next request, e.g. after the slave was power cycled
+    pub fn reset(&mut self) {
+        self.initialized = false;
+        self.attempts = 0;
+        self.pending = None;
+    }
+
+    /// Queues a REQ_UD2, preceded by SND_NKE if the link has not been initialized yet
+    pub fn read_user_data(&mut self) -> Result<(), MasterError> {
+        if self.address
+            == (Address::Broadcast {
+                reply_required: false,
+            })
+        {
+            return Err(MasterError::NoReplyExpected);
+        }
+        self.start(Request::ReadUserData)
+    }
+
+    /// Queues a SND_UD carrying `data`, starting with the CI field
+    pub fn send_user_data(&mut self, data: &[u8]) -> Result<(), MasterError> {
+        if self.pending.is_some() {
+            return Err(MasterError::Busy);
+        }
+        self.payload.clear();
+        self.payload
+            .try_extend_from_slice(data)
+            .map_err(|_| MasterError::DataTooLong { length: data.len() })?;
+        self.start(Request::SendUserData)
+    }
+
+    fn start(&mut self, request: Request) -> Result<(), MasterError> {
+        if self.pending.is_some() {
+            return Err(MasterError::Busy);
+        }
+        self.pending = Some(request);
+        self.attempts = 0;
+        Ok(())
+    }
+
+    /// The frame to send, `None` if no request is pending
+    #[must_use]
+    pub fn request(&self) -> Option<Frame<'_>> {
+        let request = self.pending?;
+        let address = self.address.clone();
+        if !self.initialized {
+            return Some(Frame::ShortFrame {
+                function: Function::SndNk,
+                address,
+            });
+        }
+        Some(match request {
+            Request::ReadUserData => Frame::ShortFrame {
+                function: Function::ReqUd2 { fcb: self.fcb },
+                address,
+            },
+            Request::SendUserData => Frame::ControlFrame {
+                function: Function::SndUd { fcb: self.fcb },
+                address,
+                data: &self.payload,
+            },
+        })
+    }
+
+    /// Consumes the response to the frame returned by [`Master::request`]
+    pub fn handle<'a>(&mut self, response: Response<'a>) -> Result<Outcome<'a>, MasterError> {
+        let request = self.pending.ok_or(MasterError::Idle)?;
+        let expects_reply = self.address
+            != (Address::Broadcast {
+                reply_required: false,
+            });
+
+        let frame = match response {
+            Response::Timeout if !expects_reply => {
+                return Ok(self.complete(request, None));
+            }
+            Response::Timeout | Response::Invalid(_) => return self.retry(),
+            Response::Frame(frame) => frame,
+        };
+
+        match (self.initialized, request, frame) {
+            (false, _, Frame::SingleCharacter { character })
+            | (true, Request::SendUserData, Frame::SingleCharacter { character })
+                if character == ACKNOWLEDGE =>
+            {
+                Ok(self.complete(request, None))
+            }
+            (
+                true,
+                Request::ReadUserData,
+                frame @ Frame::LongFrame {
+                    function: Function::RspUd { .. },
+                    ..
+                },
+            ) => {
+                if let Frame::LongFrame { address, .. } = &frame {
+                    self.validate_address(address)?;
+                }
+                Ok(self.complete(request, Some(frame)))
+            }
+            _ => self.fail(MasterError::UnexpectedResponse),
+        }
+    }
+
+    fn validate_address(&mut self, address: &Address) -> Result<(), MasterError> {
+        if let Address::Primary(expected) = self.address {
+            let received = u8::from(address);
+            if received != expected {
+                return self.fail(MasterError::AddressMismatch { expected, received });
+            }
+        }
+        Ok(())
+    }
+
+    fn complete<'a>(&mut self, request: Request, frame: Option<Frame<'a>>) -> Outcome<'a> {
+        self.attempts = 0;
+        if !self.initialized {
+            // the first request after SND_NKE is sent with the frame count bit set
+            self.initialized = true;
+            self.fcb = true;
+            return Outcome::Initialized;
+        }
+        self.fcb = !self.fcb;
+        self.pending = None;
+        match (request, frame) {
+            (
+                Request::ReadUserData,
+                Some(Frame::LongFrame {
+                    function: Function::RspUd { acd, dfc },
+                    address,
+                    data,
+                }),
+            ) => Outcome::UserData {
+                address,
+                access_demand: acd,
+                data_flow_control: dfc,
+                data,
+            },
+            _ => Outcome::Acknowledged,
+        }
+    }
+
+    fn retry<'a>(&mut self) -> Result<Outcome<'a>, MasterError> {
+        self.attempts = self.attempts.saturating_add(1);
+        if self.attempts > self.config.retries {
+            let attempts = self.attempts;
+            return self.fail(MasterError::NoResponse { attempts });
+        }
+        // a repeated request keeps the frame count bit, so the slave can resend its answer
+        Ok(Outcome::Retry)
+    }
+
+    fn fail<T>(&mut self, error: MasterError) -> Result<T, MasterError> {
+        // the state of the slave is unknown, start over with SND_NKE
+        self.reset();
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACK: Response<'static> = Response::Frame(Frame::SingleCharacter { character: 0xE5 });
+
+    fn rsp_ud(address: u8, data: &[u8]) -> Response<'_> {
+        Response::Frame(Frame::LongFrame {
+            function: Function::RspUd {
+                acd: false,
+                dfc: false,
+            },
+            address: Address::Primary(address),
+            data,
+        })
+    }
+
+    #[test]
+    fn test_response_timeout() {
+        let config = MasterConfig {
+            baud_rate: 2400,
+            retries: 0,
+        };
+        assert_eq!(config.response_timeout_ms(), 188);
+        let config = MasterConfig {
+            baud_rate: 300,
+            retries: 0,
+        };
+        assert_eq!(config.response_timeout_ms(), 1150);
+    }
+
+    #[test]
+    fn test_read_user_data_toggles_fcb() {
+        let mut master = Master::new(Address::Primary(5));
+        assert_eq!(master.request(), None);
+        master.read_user_data().unwrap();
+        assert_eq!(master.read_user_data(), Err(MasterError::Busy));
+
+        assert_eq!(
+            master.request(),
+            Some(Frame::ShortFrame {
+                function: Function::SndNk,
+                address: Address::Primary(5)
+            })
+        );
+        assert_eq!(master.handle(ACK), Ok(Outcome::Initialized));
+
+        let data = [0x72, 0x01];
+        for fcb in [true, false, true] {
+            assert_eq!(
+                master.request(),
+                Some(Frame::ShortFrame {
+                    function: Function::ReqUd2 { fcb },
+                    address: Address::Primary(5)
+                })
+            );
+            assert_eq!(
+                master.handle(rsp_ud(5, &data)),
+                Ok(Outcome::UserData {
+                    address: Address::Primary(5),
+                    access_demand: false,
+                    data_flow_control: false,
+                    data: &data,
+                })
+            );
+            assert_eq!(master.request(), None);
+            master.read_user_data().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_retry_keeps_fcb() {
+        let mut master = Master::with_config(
+            Address::Primary(1),
+            MasterConfig {
+                baud_rate: 2400,
+                retries: 1,
+            },
+        );
+        master.read_user_data().unwrap();
+        assert_eq!(master.handle(ACK), Ok(Outcome::Initialized));
+        assert_eq!(master.handle(Response::Timeout), Ok(Outcome::Retry));
+        assert_eq!(
+            master.request(),
+            Some(Frame::ShortFrame {
+                function: Function::ReqUd2 { fcb: true },
+                address: Address::Primary(1)
+            })
+        );
+        assert_eq!(
+            master.handle(Response::Invalid(FrameError::WrongChecksum {
+                expected: 0,
+                actual: 1
+            })),
+            Err(MasterError::NoResponse { attempts: 2 })
+        );
+        // the link is initialized again after a failed request
+        assert!(!master.is_busy());
+        master.read_user_data().unwrap();
+        assert_eq!(
+            master.request(),
+            Some(Frame::ShortFrame {
+                function: Function::SndNk,
+                address: Address::Primary(1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_validates_response() {
+        let mut master = Master::new(Address::Primary(1));
+        master.read_user_data().unwrap();
+        assert_eq!(master.handle(ACK), Ok(Outcome::Initialized));
+        assert_eq!(
+            master.handle(rsp_ud(2, &[0x72])),
+            Err(MasterError::AddressMismatch {
+                expected: 1,
+                received: 2
+            })
+        );
+
+        master.read_user_data().unwrap();
+        assert_eq!(master.handle(ACK), Ok(Outcome::Initialized));
+        // a request of another master is not an answer
+        assert_eq!(
+            master.handle(Response::Frame(Frame::ShortFrame {
+                function: Function::ReqUd2 { fcb: true },
+                address: Address::Primary(1)
+            })),
+            Err(MasterError::UnexpectedResponse)
+        );
+        assert_eq!(master.handle(ACK), Err(MasterError::Idle));
+    }
+
+    #[test]
+    fn test_send_user_data() {
+        let mut master = Master::new(Address::Primary(1));
+        let data = [0x51, 0x01, 0x7A, 0x08];
+        master.send_user_data(&data).unwrap();
+        assert_eq!(master.handle(ACK), Ok(Outcome::Initialized));
+        assert_eq!(
+            master.request(),
+            Some(Frame::ControlFrame {
+                function: Function::SndUd { fcb: true },
+                address: Address::Primary(1),
+                data: &data
+            })
+        );
+        assert_eq!(master.handle(ACK), Ok(Outcome::Acknowledged));
+        assert_eq!(
+            master.send_user_data(&[0; MAX_FRAME_DATA_LENGTH + 1]),
+            Err(MasterError::DataTooLong {
+                length: MAX_FRAME_DATA_LENGTH + 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_broadcast_without_reply() {
+        let mut master = Master::new(Address::Broadcast {
+            reply_required: false,
+        });
+        assert_eq!(master.read_user_data(), Err(MasterError::NoReplyExpected));
+        master.send_user_data(&[0x50]).unwrap();
+        assert_eq!(master.handle(Response::Timeout), Ok(Outcome::Initialized));
+        assert_eq!(master.handle(Response::Timeout), Ok(Outcome::Acknowledged));
+    }
+}
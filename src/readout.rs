@@ -0,0 +1,353 @@
+//! Assembly of readouts spread over several RSP_UD telegrams
+//!
+//! A slave signals with the DIF 0x1F that more records follow. The master then has to send
+//! another REQ_UD2 with toggled FCB, see [`crate::master::Master`], and the records of all
+//! telegrams form a single logical readout.
+use arrayvec::ArrayVec;
+
+use crate::frames::{Frame, Function, MAX_FRAME_DATA_LENGTH};
+use crate::user_data::{
+    ApplicationLayerError, DataRecords, FixedDataHeader, SecondaryAddress, UserDataBlock,
+};
+
+/// Records of up to four full telegrams
+pub const DEFAULT_READOUT_CAPACITY: usize = 4 * MAX_FRAME_DATA_LENGTH;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AssemblerError {
+    ApplicationLayerError(ApplicationLayerError),
+    /// Only RSP_UD long frames with a variable data structure can be assembled
+    UnexpectedFrame,
+    AddressMismatch {
+        expected: SecondaryAddress,
+        received: SecondaryAddress,
+    },
+    /// A telegram is missing, the readout has to be started over
+    AccessNumberGap {
+        expected: u8,
+        received: u8,
+    },
+    BufferFull {
+        required: usize,
+    },
+    /// The last telegram did not announce more records, call `reset` for a new readout
+    AlreadyComplete,
+}
+
+impl From<ApplicationLayerError> for AssemblerError {
+    fn from(error: ApplicationLayerError) -> Self {
+        Self::ApplicationLayerError(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for AssemblerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssemblerError::ApplicationLayerError(error) => write!(f, "{}", error),
+            AssemblerError::UnexpectedFrame => write!(f, "Unexpected frame"),
+            AssemblerError::AddressMismatch { expected, received } => {
+                write!(
+                    f,
+                    "Address mismatch: expected {}, received {}",
+                    expected, received
+                )
+            }
+            AssemblerError::AccessNumberGap { expected, received } => {
+                write!(
+                    f,
+                    "Access number gap: expected {}, received {}",
+                    expected, received
+                )
+            }
+            AssemblerError::BufferFull { required } => {
+                write!(f, "Buffer full, required: {}", required)
+            }
+            AssemblerError::AlreadyComplete => write!(f, "Readout already complete"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssemblerError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Progress {
+    /// Request the next telegram with toggled FCB
+    MoreRecordsFollow,
+    /// The telegram was already received, e.g. after a repeated request with the same FCB
+    Repeated,
+    Complete,
+}
+
+/// Collects the records of successive telegrams of one slave
+#[derive(Debug)]
+pub struct ReadoutAssembler<const N: usize = DEFAULT_READOUT_CAPACITY> {
+    fixed_data_header: Option<FixedDataHeader>,
+    secondary_address: Option<SecondaryAddress>,
+    access_number: u8,
+    telegrams: usize,
+    complete: bool,
+    records: ArrayVec<u8, N>,
+}
+
+impl<const N: usize> Default for ReadoutAssembler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ReadoutAssembler<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fixed_data_header: None,
+            secondary_address: None,
+            access_number: 0,
+            telegrams: 0,
+            complete: false,
+            records: ArrayVec::new_const(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Adds the next RSP_UD telegram of the readout
+    pub fn push(&mut self, frame: &Frame<'_>) -> Result<Progress, AssemblerError> {
+        let Frame::LongFrame {
+            function: Function::RspUd { .. },
+            data,
+            ..
+        } = frame
+        else {
+            return Err(AssemblerError::UnexpectedFrame);
+        };
+        let UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        } = UserDataBlock::try_from(*data)?
+        else {
+            return Err(AssemblerError::UnexpectedFrame);
+        };
+
+        if self.complete {
+            return Err(AssemblerError::AlreadyComplete);
+        }
+
+        let received = SecondaryAddress::from(&fixed_data_header);
+        if let Some(expected) = self.secondary_address {
+            if expected != received {
+                return Err(AssemblerError::AddressMismatch { expected, received });
+            }
+            if fixed_data_header.access_number == self.access_number {
+                return Ok(Progress::Repeated);
+            }
+            let expected = self.access_number.wrapping_add(1);
+            if fixed_data_header.access_number != expected {
+                return Err(AssemblerError::AccessNumberGap {
+                    expected,
+                    received: fixed_data_header.access_number,
+                });
+            }
+        }
+
+        let records = DataRecords::new(variable_data_block, Some(&fixed_data_header));
+        let more_records_follow = records.more_records_follow();
+        let record_data = records.record_data();
+        self.records
+            .try_extend_from_slice(record_data)
+            .map_err(|_| AssemblerError::BufferFull {
+                required: self.records.len() + record_data.len(),
+            })?;
+
+        self.access_number = fixed_data_header.access_number;
+        self.secondary_address = Some(received);
+        self.telegrams += 1;
+        if self.fixed_data_header.is_none() {
+            self.fixed_data_header = Some(fixed_data_header);
+        }
+
+        if more_records_follow {
+            Ok(Progress::MoreRecordsFollow)
+        } else {
+            self.complete = true;
+            Ok(Progress::Complete)
+        }
+    }
+
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Number of telegrams merged so far
+    #[must_use]
+    pub const fn telegrams(&self) -> usize {
+        self.telegrams
+    }
+
+    /// Header of the first telegram of the readout
+    #[must_use]
+    pub const fn fixed_data_header(&self) -> Option<&FixedDataHeader> {
+        self.fixed_data_header.as_ref()
+    }
+
+    #[must_use]
+    pub const fn secondary_address(&self) -> Option<&SecondaryAddress> {
+        self.secondary_address.as_ref()
+    }
+
+    /// The records of all telegrams received so far
+    #[must_use]
+    pub fn records(&self) -> DataRecords<'_> {
+        DataRecords::new(&self.records, self.fixed_data_header.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frames::Address;
+    use crate::user_data::data_information::DataType;
+
+    fn telegram(access_number: u8, records: &[u8], buffer: &mut [u8; 64]) -> usize {
+        let header = [
+            0x72,
+            0x78,
+            0x56,
+            0x34,
+            0x12,
+            0xE6,
+            0x1E,
+            0x01,
+            0x07,
+            access_number,
+            0x00,
+            0x00,
+            0x00,
+        ];
+        buffer[..header.len()].copy_from_slice(&header);
+        buffer[header.len()..header.len() + records.len()].copy_from_slice(records);
+        header.len() + records.len()
+    }
+
+    fn rsp_ud(data: &[u8]) -> Frame<'_> {
+        Frame::LongFrame {
+            function: Function::RspUd {
+                acd: false,
+                dfc: false,
+            },
+            address: Address::Primary(5),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_more_records_follow() {
+        let records = [0x01, 0x13, 0x05, 0x1F, 0xAA, 0xBB];
+        let data_records = DataRecords::from(records.as_slice());
+        assert!(data_records.more_records_follow());
+        assert_eq!(data_records.record_data(), &[0x01, 0x13, 0x05]);
+        assert_eq!(data_records.count(), 2);
+
+        let data_records = DataRecords::from(&records[..3]);
+        assert!(!data_records.more_records_follow());
+        assert_eq!(data_records.record_data(), &[0x01, 0x13, 0x05]);
+    }
+
+    #[test]
+    fn test_assemble_readout() {
+        let mut assembler = ReadoutAssembler::<64>::new();
+        let mut buffer = [0u8; 64];
+
+        let length = telegram(7, &[0x01, 0x13, 0x05, 0x1F], &mut buffer);
+        let frame = rsp_ud(&buffer[..length]);
+        assert!(crate::MbusData::from(rsp_ud(&buffer[..length])).more_records_follow());
+        assert_eq!(assembler.push(&frame), Ok(Progress::MoreRecordsFollow));
+        assert_eq!(assembler.push(&frame), Ok(Progress::Repeated));
+
+        let length = telegram(8, &[0x01, 0x13, 0x06, 0x2F, 0x2F], &mut buffer);
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Ok(Progress::Complete)
+        );
+        assert!(assembler.is_complete());
+        assert_eq!(assembler.telegrams(), 2);
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Err(AssemblerError::AlreadyComplete)
+        );
+
+        let mut records = assembler.records().flatten();
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(5.0))
+        );
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(6.0))
+        );
+        assert!(records.next().is_none());
+        assert_eq!(assembler.fixed_data_header().unwrap().access_number, 7);
+    }
+
+    #[test]
+    fn test_access_number_gap() {
+        let mut assembler = ReadoutAssembler::<64>::new();
+        let mut buffer = [0u8; 64];
+        let length = telegram(0xFF, &[0x01, 0x13, 0x05, 0x1F], &mut buffer);
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Ok(Progress::MoreRecordsFollow)
+        );
+        let length = telegram(0x01, &[0x01, 0x13, 0x06], &mut buffer);
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Err(AssemblerError::AccessNumberGap {
+                expected: 0x00,
+                received: 0x01
+            })
+        );
+        // the access number wraps around
+        let length = telegram(0x00, &[0x01, 0x13, 0x06], &mut buffer);
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Ok(Progress::Complete)
+        );
+    }
+
+    #[test]
+    fn test_address_mismatch_and_capacity() {
+        let mut assembler = ReadoutAssembler::<4>::new();
+        let mut buffer = [0u8; 64];
+        let length = telegram(1, &[0x01, 0x13, 0x05, 0x1F], &mut buffer);
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Ok(Progress::MoreRecordsFollow)
+        );
+
+        let length = telegram(2, &[0x01, 0x13, 0x06], &mut buffer);
+        buffer[1] = 0x79;
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Err(AssemblerError::AddressMismatch {
+                expected: SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07),
+                received: SecondaryAddress::new(0x12345679, 0x1EE6, 0x01, 0x07),
+            })
+        );
+
+        buffer[1] = 0x78;
+        assert_eq!(
+            assembler.push(&rsp_ud(&buffer[..length])),
+            Err(AssemblerError::BufferFull { required: 6 })
+        );
+        assert_eq!(
+            assembler.push(&Frame::SingleCharacter { character: 0xE5 }),
+            Err(AssemblerError::UnexpectedFrame)
+        );
+    }
+}
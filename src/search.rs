@@ -0,0 +1,409 @@
+//! Sans-IO implementation of the secondary address wildcard search
+//!
+//! The search starts by selecting all slaves with the `FFFFFFFF` wildcard. Whenever more
+//! than one slave answers the selection, the identification number is narrowed down digit by
+//! digit, starting with the most significant one, until a single slave is selected and can be
+//! read out. Slaves sharing an identification number are then told apart by the medium, the
+//! version and finally the manufacturer. Sending the frames and waiting for the responses is
+//! left to the caller.
+use crate::frames::{Address, Frame, FrameError, Function};
+use crate::user_data::{SecondaryAddress, UserDataBlock};
+
+const SELECT_SLAVE: u8 = 0x52;
+
+/// Narrowing levels following the eight identification number digits
+const MEDIUM: u8 = 9;
+const VERSION: u8 = 10;
+const MANUFACTURER: u8 = 11;
+
+/// Manufacturer code of `AAA`, the first of the three letter codes
+const FIRST_MANUFACTURER: u16 = 0x0421;
+
+/// What the bus returned after a request of the search was sent
+#[derive(Debug, PartialEq)]
+pub enum SearchResponse<'a> {
+    /// Nothing was received within the response timeout
+    Timeout,
+    /// Something was received but could not be decoded, i.e. several slaves answered at once
+    Collision,
+    Frame(Frame<'a>),
+}
+
+impl<'a> From<Result<Frame<'a>, FrameError>> for SearchResponse<'a> {
+    fn from(result: Result<Frame<'a>, FrameError>) -> Self {
+        match result {
+            Ok(frame) => Self::Frame(frame),
+            Err(_) => Self::Collision,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Select,
+    ReadOut,
+    Finished,
+}
+
+/// State machine enumerating all secondary addresses on a segment
+///
+/// Slaves sharing the same identification number are told apart by selecting each medium and
+/// version in turn and, if they still collide, each three letter manufacturer code. The latter
+/// takes up to 17576 selections and is only needed if the slaves agree on everything else.
+#[derive(Debug, Clone)]
+pub struct SecondaryAddressSearch {
+    mask: SecondaryAddress,
+    /// Number of narrowed fields, the identification number digits followed by medium,
+    /// version and manufacturer
+    fixed_fields: u8,
+    state: State,
+    payload: [u8; 9],
+}
+
+impl Default for SecondaryAddressSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecondaryAddressSearch {
+    #[must_use]
+    pub const fn new() -> Self {
+        let mask = SecondaryAddress::WILDCARD;
+        Self {
+            mask,
+            fixed_fields: 0,
+            state: State::Select,
+            payload: select_payload(&mask),
+        }
+    }
+
+    /// The selection mask of the current step
+    #[must_use]
+    pub const fn mask(&self) -> &SecondaryAddress {
+        &self.mask
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.state == State::Finished
+    }
+
+    /// The next frame to send, `None` once every address has been enumerated
+    #[must_use]
+    pub fn request(&self) -> Option<Frame<'_>> {
+        match self.state {
+            State::Select => Some(Frame::ControlFrame {
+                function: Function::SndUd { fcb: false },
+                address: Address::Secondary,
+                data: &self.payload,
+            }),
+            State::ReadOut => Some(Frame::ShortFrame {
+                function: Function::ReqUd2 { fcb: false },
+                address: Address::Secondary,
+            }),
+            State::Finished => None,
+        }
+    }
+
+    /// Consumes the response to the last request, returns the address of a slave once it has
+    /// been read out
+    pub fn handle(&mut self, response: SearchResponse<'_>) -> Option<SecondaryAddress> {
+        match (self.state, response) {
+            (State::Select, SearchResponse::Frame(Frame::SingleCharacter { character: 0xE5 })) => {
+                // identical acknowledges of several slaves overlap without corruption, so
+                // the read out has to tell if the selection is unique
+                self.state = State::ReadOut;
+                None
+            }
+            (State::Select, SearchResponse::Timeout) => {
+                self.advance();
+                None
+            }
+            (State::Select | State::ReadOut, SearchResponse::Collision) => {
+                self.narrow();
+                None
+            }
+            (
+                State::ReadOut,
+                SearchResponse::Frame(Frame::LongFrame {
+                    function: Function::RspUd { .. },
+                    data,
+                    ..
+                }),
+            ) => {
+                self.advance();
+                match UserDataBlock::try_from(data) {
+                    Ok(UserDataBlock::VariableDataStructure {
+                        fixed_data_header, ..
+                    }) => Some(SecondaryAddress::from(&fixed_data_header)),
+                    _ => None,
+                }
+            }
+            (State::Select | State::ReadOut, _) => {
+                self.advance();
+                None
+            }
+            (State::Finished, _) => None,
+        }
+    }
+
+    /// Fixes the next digit of the identification number, starting with 0, and once all digits
+    /// are fixed the medium, version and manufacturer
+    fn narrow(&mut self) {
+        if self.fixed_fields >= MANUFACTURER {
+            self.advance();
+            return;
+        }
+        self.fixed_fields += 1;
+        let first = if self.fixed_fields == MANUFACTURER {
+            FIRST_MANUFACTURER
+        } else {
+            0
+        };
+        self.set_field(first);
+    }
+
+    /// Moves on to the next field value, going back up once all values of a field are done
+    fn advance(&mut self) {
+        while self.fixed_fields > 0 {
+            if let Some(next) = self.next_value() {
+                self.set_field(next);
+                return;
+            }
+            self.set_field(0xFFFF);
+            self.fixed_fields -= 1;
+        }
+        self.state = State::Finished;
+    }
+
+    fn next_value(&self) -> Option<u16> {
+        match self.fixed_fields {
+            MEDIUM if self.mask.medium < 0xFE => Some(u16::from(self.mask.medium) + 1),
+            VERSION if self.mask.version < 0xFE => Some(u16::from(self.mask.version) + 1),
+            MEDIUM | VERSION => None,
+            MANUFACTURER => next_manufacturer(self.mask.manufacturer),
+            _ => {
+                let digit = (self.mask.identification_number >> self.shift()) & 0xF;
+                // digits are BCD, the value fits into u16
+                (digit < 9).then_some(digit as u16 + 1)
+            }
+        }
+    }
+
+    const fn shift(&self) -> u32 {
+        (8 - self.fixed_fields as u32) * 4
+    }
+
+    /// Sets the field of the current level, `0xFFFF` restores its wildcard
+    fn set_field(&mut self, value: u16) {
+        match self.fixed_fields {
+            MEDIUM => self.mask.medium = value.to_le_bytes()[0],
+            VERSION => self.mask.version = value.to_le_bytes()[0],
+            MANUFACTURER => self.mask.manufacturer = value,
+            _ => {
+                let shift = self.shift();
+                self.mask.identification_number = (self.mask.identification_number
+                    & !(0xF << shift))
+                    | ((u32::from(value) & 0xF) << shift);
+            }
+        }
+        self.payload = select_payload(&self.mask);
+        self.state = State::Select;
+    }
+}
+
+/// The next three letter manufacturer code, each letter is stored in five bits from 1 (`A`)
+/// to 26 (`Z`)
+const fn next_manufacturer(code: u16) -> Option<u16> {
+    let mut code = code;
+    let mut shift = 0;
+    while shift <= 10 {
+        if (code >> shift) & 0x1F < 26 {
+            return Some(code + (1 << shift));
+        }
+        // wrap the letter around to `A` and carry into the next one
+        code = (code & !(0x1F << shift)) | (1 << shift);
+        shift += 5;
+    }
+    None
+}
+
+const fn select_payload(mask: &SecondaryAddress) -> [u8; 9] {
+    let bytes = mask.to_bytes();
+    [
+        SELECT_SLAVE,
+        bytes[0],
+        bytes[1],
+        bytes[2],
+        bytes[3],
+        bytes[4],
+        bytes[5],
+        bytes[6],
+        bytes[7],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrayvec::ArrayVec;
+
+    /// Slaves answering the way a real segment would, including collisions
+    struct Bus<'a> {
+        slaves: &'a [SecondaryAddress],
+        selected: ArrayVec<SecondaryAddress, 8>,
+        requests: usize,
+    }
+
+    impl<'a> Bus<'a> {
+        fn new(slaves: &'a [SecondaryAddress]) -> Self {
+            Self {
+                slaves,
+                selected: ArrayVec::new(),
+                requests: 0,
+            }
+        }
+
+        fn transact<'b>(&mut self, request: Frame<'_>, buffer: &'b mut [u8]) -> SearchResponse<'b> {
+            self.requests += 1;
+            match request {
+                Frame::ControlFrame { data, .. } => {
+                    let Ok(UserDataBlock::SelectSlave { secondary_address }) =
+                        UserDataBlock::try_from(data)
+                    else {
+                        panic!("unexpected control frame");
+                    };
+                    self.selected = self
+                        .slaves
+                        .iter()
+                        .filter(|slave| secondary_address.matches(slave))
+                        .copied()
+                        .collect();
+                    if self.selected.is_empty() {
+                        SearchResponse::Timeout
+                    } else {
+                        SearchResponse::Frame(Frame::SingleCharacter { character: 0xE5 })
+                    }
+                }
+                Frame::ShortFrame { .. } => match self.selected.as_slice() {
+                    [] => SearchResponse::Timeout,
+                    [slave] => {
+                        let mut data = [0u8; 13];
+                        data[0] = 0x72;
+                        data[1..9].copy_from_slice(&slave.to_bytes());
+                        let frame = Frame::LongFrame {
+                            function: Function::RspUd {
+                                acd: false,
+                                dfc: false,
+                            },
+                            address: Address::Primary(0),
+                            data: &data,
+                        };
+                        let length = frame.encode_into(buffer).unwrap();
+                        SearchResponse::from(Frame::try_from(&buffer[..length]))
+                    }
+                    _ => SearchResponse::Collision,
+                },
+                _ => panic!("unexpected request"),
+            }
+        }
+    }
+
+    fn run(slaves: &[SecondaryAddress]) -> (ArrayVec<SecondaryAddress, 8>, usize) {
+        let mut bus = Bus::new(slaves);
+        let mut search = SecondaryAddressSearch::new();
+        let mut found = ArrayVec::new();
+        let mut buffer = [0u8; 32];
+        while let Some(request) = search.request() {
+            let response = bus.transact(request, &mut buffer);
+            if let Some(address) = search.handle(response) {
+                found.push(address);
+            }
+        }
+        (found, bus.requests)
+    }
+
+    #[test]
+    fn test_search_empty_bus() {
+        let (found, requests) = run(&[]);
+        assert!(found.is_empty());
+        assert_eq!(requests, 1);
+    }
+
+    #[test]
+    fn test_search_single_slave() {
+        let slave = SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07);
+        let (found, requests) = run(&[slave]);
+        assert_eq!(found.as_slice(), &[slave]);
+        // select and read out
+        assert_eq!(requests, 2);
+    }
+
+    #[test]
+    fn test_search_collisions() {
+        let slaves = [
+            SecondaryAddress::new(0x87654321, 0x1EE6, 0x01, 0x07),
+            SecondaryAddress::new(0x12345679, 0x1EE6, 0x01, 0x07),
+            SecondaryAddress::new(0x12345678, 0x0442, 0x02, 0x04),
+            SecondaryAddress::new(0x12340000, 0x1EE6, 0x01, 0x07),
+            SecondaryAddress::new(0x00000001, 0x1EE6, 0x01, 0x07),
+        ];
+        let (found, _) = run(&slaves);
+        assert_eq!(
+            found.as_slice(),
+            &[slaves[4], slaves[3], slaves[2], slaves[1], slaves[0]]
+        );
+    }
+
+    #[test]
+    fn test_search_shared_identification_number() {
+        let slaves = [
+            SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07),
+            SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x04),
+            SecondaryAddress::new(0x12345678, 0x1EE6, 0x02, 0x07),
+            SecondaryAddress::new(0x12345678, 0x0442, 0x01, 0x07),
+        ];
+        let (found, _) = run(&slaves);
+        assert_eq!(
+            found.as_slice(),
+            &[slaves[1], slaves[3], slaves[0], slaves[2]]
+        );
+    }
+
+    #[test]
+    fn test_search_identical_addresses() {
+        let slave = SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07);
+        let other = SecondaryAddress::new(0x00000001, 0x1EE6, 0x01, 0x07);
+        let (found, _) = run(&[slave, slave, other]);
+        assert_eq!(found.as_slice(), &[other]);
+    }
+
+    #[test]
+    fn test_next_manufacturer() {
+        assert_eq!(next_manufacturer(FIRST_MANUFACTURER), Some(0x0422));
+        // GWZ wraps around to GXA
+        assert_eq!(next_manufacturer(0x1EFA), Some(0x1F01));
+        assert_eq!(next_manufacturer(0x6B5A), None);
+    }
+
+    #[test]
+    fn test_search_ignores_unexpected_responses() {
+        let mut search = SecondaryAddressSearch::new();
+        search.handle(SearchResponse::Collision);
+        assert_eq!(search.mask().identification_number, 0x0FFFFFFF);
+        // a short frame is not a valid answer to the selection, move on to the next digit
+        search.handle(SearchResponse::Frame(Frame::ShortFrame {
+            function: Function::SndNk,
+            address: Address::Primary(1),
+        }));
+        assert_eq!(search.mask().identification_number, 0x1FFFFFFF);
+        for _ in 1..9 {
+            search.handle(SearchResponse::Timeout);
+        }
+        assert_eq!(search.mask().identification_number, 0x9FFFFFFF);
+        search.handle(SearchResponse::Timeout);
+        assert!(search.is_finished());
+        assert_eq!(search.request(), None);
+    }
+}
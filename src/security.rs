@@ -0,0 +1,831 @@
+//! Decryption of encrypted application layer payloads (EN 13757-7, OMS security profiles)
+//!
+//! The security mode and the number of encrypted blocks are announced in the
+//! [`ConfigurationField`] of the transport layer header.
+//! Payloads decrypted in mode 5 and 7 start with the check bytes 0x2F 0x2F.
+//!
+//! * Mode 5: AES-128-CBC with the meter key, the IV is derived from the header
+//! * Mode 7: AES-128-CBC with a zero IV and a session key derived with AES-CMAC from the meter
+//!   key and the message counter, the message is authenticated by a truncated AES-CMAC in the
+//!   authentication and fragmentation layer (AFL). The MAC is verified before decrypting.
+//! * Mode 9: AES-128-GCM with the meter key, the nonce is derived from the address and the
+//!   message counter of the AFL, the headers are authenticated as additional data and the
+//!   truncated tag is carried in the AFL. A wrong key can not be told apart from an altered
+//!   message and is reported as a MAC mismatch.
+use aes::cipher::{BlockDecryptMut, KeyInit, KeyIvInit};
+use aes::Aes128;
+use aes_gcm::aead::consts::U12;
+use aes_gcm::aead::AeadInPlace;
+use aes_gcm::{AesGcm, Nonce, Tag};
+use cmac::{Cmac, Mac};
+
+use crate::user_data::configuration_field::SecurityMode;
+use crate::user_data::{ConfigurationField, DataRecords, FixedDataHeader, SecondaryAddress};
+
+/// AES block size, encrypted payloads are a multiple of it
+pub const BLOCK_SIZE: usize = 16;
+
+/// Caller provided 128-bit AES key of a meter
+pub type Key = [u8; 16];
+
+type Aes128CbcDec = cbc::Decryptor<Aes128>;
+
+/// AES-GCM with a 96 bit nonce and a tag truncated to 12 bytes
+type Aes128Gcm = AesGcm<Aes128, U12, U12>;
+
+/// Derivation constant of the encryption key for messages sent by the meter
+const DERIVATION_ENCRYPTION: u8 = 0x00;
+/// Derivation constant of the MAC key for messages sent by the meter
+const DERIVATION_MAC: u8 = 0x01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecryptionError {
+    UnsupportedMode {
+        mode: SecurityMode,
+    },
+    /// The announced encrypted blocks exceed the payload
+    InvalidLength {
+        length: usize,
+    },
+    BufferTooSmall {
+        required: usize,
+    },
+    /// The decrypted payload does not start with the 0x2F2F check bytes, usually the key
+    /// is wrong
+    WrongKey,
+    /// The MAC of the AFL does not match, the message was altered or the key is wrong
+    MacMismatch,
+    /// Mode 7 and 9 require the message counter and MAC of the AFL
+    MissingAuthentication,
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for DecryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptionError::UnsupportedMode { mode } => {
+                write!(f, "Unsupported security mode: {}", mode)
+            }
+            DecryptionError::InvalidLength { length } => {
+                write!(f, "Invalid length of encrypted data: {}", length)
+            }
+            DecryptionError::BufferTooSmall { required } => {
+                write!(f, "Buffer too small, required: {}", required)
+            }
+            DecryptionError::WrongKey => write!(f, "Decryption check failed, wrong key"),
+            DecryptionError::MacMismatch => write!(f, "MAC mismatch"),
+            DecryptionError::MissingAuthentication => {
+                write!(f, "Missing authentication and fragmentation layer")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecryptionError {}
+
+/// Fields of the authentication and fragmentation layer protecting a mode 7 or 9 message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Authentication<'a> {
+    /// AFL fields covered by the MAC, see
+    /// [`crate::afl::AuthenticationFragmentationLayer::authenticated_fields`]
+    pub fields: &'a [u8],
+    /// Message counter (AFL.MCR)
+    pub message_counter: u32,
+    /// Truncated AES-CMAC or AES-GCM tag (AFL.MAC)
+    pub mac: &'a [u8],
+    /// Transport and application layer following the AFL, starting with the CI field
+    pub message: &'a [u8],
+}
+
+/// Initialisation vector of mode 5: manufacturer, identification number, version and medium
+/// followed by the access number repeated eight times
+#[must_use]
+pub fn mode5_iv(address: &SecondaryAddress, access_number: u8) -> [u8; BLOCK_SIZE] {
+    let bytes = address.to_bytes();
+    let mut iv = [access_number; BLOCK_SIZE];
+    iv[0] = bytes[4];
+    iv[1] = bytes[5];
+    iv[2] = bytes[0];
+    iv[3] = bytes[1];
+    iv[4] = bytes[2];
+    iv[5] = bytes[3];
+    iv[6] = bytes[6];
+    iv[7] = bytes[7];
+    iv
+}
+
+/// Nonce of mode 9: manufacturer, identification number, version and medium followed by the
+/// message counter
+#[must_use]
+pub fn mode9_nonce(address: &SecondaryAddress, message_counter: u32) -> [u8; 12] {
+    let mut nonce = [0; 12];
+    nonce[..8].copy_from_slice(&mode5_iv(address, 0)[..8]);
+    nonce[8..].copy_from_slice(&message_counter.to_le_bytes());
+    nonce
+}
+
+fn cmac(key: &Key, parts: &[&[u8]]) -> Cmac<Aes128> {
+    let mut mac = <Cmac<Aes128> as KeyInit>::new(key.into());
+    for part in parts {
+        mac.update(part);
+    }
+    mac
+}
+
+/// Derives a session key with AES-CMAC (KDF-A) from the meter key, the message counter and
+/// the identification number of the meter
+#[must_use]
+pub fn derive_key(
+    key: &Key,
+    derivation_constant: u8,
+    message_counter: u32,
+    identification_number: u32,
+) -> Key {
+    let mut input = [0x07; BLOCK_SIZE];
+    input[0] = derivation_constant;
+    input[1..5].copy_from_slice(&message_counter.to_le_bytes());
+    input[5..9].copy_from_slice(&identification_number.to_le_bytes());
+    cmac(key, &[&input]).finalize().into_bytes().into()
+}
+
+fn decrypt_cbc(
+    key: &Key,
+    iv: &[u8; BLOCK_SIZE],
+    configuration: &ConfigurationField,
+    plaintext: &mut [u8],
+) -> Result<(), DecryptionError> {
+    let length = configuration.encrypted_length(plaintext.len());
+    if length == 0 {
+        return Ok(());
+    }
+    let encrypted = plaintext
+        .get_mut(..length)
+        .ok_or(DecryptionError::InvalidLength { length })?;
+    let mut decryptor = Aes128CbcDec::new(key.into(), iv.into());
+    for block in encrypted.chunks_exact_mut(BLOCK_SIZE) {
+        decryptor.decrypt_block_mut(block.into());
+    }
+    if encrypted.starts_with(&[0x2F, 0x2F]) {
+        Ok(())
+    } else {
+        Err(DecryptionError::WrongKey)
+    }
+}
+
+/// Decrypts the payload following the transport layer header into `buffer` and returns the
+/// length of the plaintext. Unencrypted trailing data is copied as is, payloads without
+/// encryption (mode 0) are copied unchanged.
+///
+/// In mode 7 the payload starts with the configuration field extension, which is not part of
+/// the plaintext. The MAC is verified before decrypting, so an altered message as well as a
+/// wrong key are reported as a MAC mismatch.
+///
+/// In mode 9 the whole payload is encrypted, the additional data are the AFL fields and the
+/// transport layer header of the message. The plaintext carries no check bytes and is returned
+/// once the tag is verified.
+pub fn decrypt(
+    address: &SecondaryAddress,
+    access_number: u8,
+    configuration: &ConfigurationField,
+    data: &[u8],
+    key: &Key,
+    authentication: Option<&Authentication<'_>>,
+    buffer: &mut [u8],
+) -> Result<usize, DecryptionError> {
+    let data = match configuration.security_mode {
+        SecurityMode::AesCbcZeroIv => data
+            .get(1..)
+            .ok_or(DecryptionError::InvalidLength { length: data.len() })?,
+        _ => data,
+    };
+    let plaintext = buffer
+        .get_mut(..data.len())
+        .ok_or(DecryptionError::BufferTooSmall {
+            required: data.len(),
+        })?;
+    plaintext.copy_from_slice(data);
+
+    match configuration.security_mode {
+        SecurityMode::NoEncryption => {}
+        SecurityMode::AesCbc => decrypt_cbc(
+            key,
+            &mode5_iv(address, access_number),
+            configuration,
+            plaintext,
+        )?,
+        SecurityMode::AesCbcZeroIv => {
+            let authentication = authentication.ok_or(DecryptionError::MissingAuthentication)?;
+            let derive = |constant| {
+                derive_key(
+                    key,
+                    constant,
+                    authentication.message_counter,
+                    address.identification_number,
+                )
+            };
+            cmac(
+                &derive(DERIVATION_MAC),
+                &[authentication.fields, authentication.message],
+            )
+            .verify_truncated_left(authentication.mac)
+            .map_err(|_| DecryptionError::MacMismatch)?;
+            decrypt_cbc(
+                &derive(DERIVATION_ENCRYPTION),
+                &[0; BLOCK_SIZE],
+                configuration,
+                plaintext,
+            )?;
+        }
+        SecurityMode::AesGcm => {
+            let authentication = authentication.ok_or(DecryptionError::MissingAuthentication)?;
+            let header = authentication
+                .message
+                .len()
+                .checked_sub(data.len())
+                .and_then(|length| authentication.message.get(..length))
+                .ok_or(DecryptionError::InvalidLength { length: data.len() })?;
+            if authentication.mac.len() != 12 {
+                return Err(DecryptionError::MacMismatch);
+            }
+            let mut aad = [0u8; 2 * BLOCK_SIZE];
+            let aad = aad
+                .get_mut(..authentication.fields.len() + header.len())
+                .ok_or(DecryptionError::InvalidLength {
+                    length: header.len(),
+                })?;
+            let (fields, rest) = aad.split_at_mut(authentication.fields.len());
+            fields.copy_from_slice(authentication.fields);
+            rest.copy_from_slice(header);
+            Aes128Gcm::new(key.into())
+                .decrypt_in_place_detached(
+                    Nonce::from_slice(&mode9_nonce(address, authentication.message_counter)),
+                    aad,
+                    plaintext,
+                    Tag::<U12>::from_slice(authentication.mac),
+                )
+                .map_err(|_| DecryptionError::MacMismatch)?;
+        }
+        mode => return Err(DecryptionError::UnsupportedMode { mode }),
+    }
+    Ok(data.len())
+}
+
+/// Decrypts the variable data block of a response with the given header and returns its
+/// records
+pub fn decrypt_records<'a>(
+    fixed_data_header: &'a FixedDataHeader,
+    variable_data_block: &[u8],
+    key: &Key,
+    authentication: Option<&Authentication<'_>>,
+    buffer: &'a mut [u8],
+) -> Result<DataRecords<'a>, DecryptionError> {
+    let length = decrypt(
+        &SecondaryAddress::from(fixed_data_header),
+        fixed_data_header.access_number,
+        &fixed_data_header.configuration_field,
+        variable_data_block,
+        key,
+        authentication,
+        buffer,
+    )?;
+    let plaintext = buffer
+        .get(..length)
+        .ok_or(DecryptionError::BufferTooSmall { required: length })?;
+    Ok(DataRecords::new(plaintext, Some(fixed_data_header)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_data::data_information::DataType;
+    use crate::user_data::UserDataBlock;
+
+    // example of security profile A in OMS Vol. 2 Annex N: key, and the header and encrypted
+    // payload of the wM-Bus frame 2E44931578563412330333637A2A0020055923C95AAA26D1B2E7...
+    // with the link layer address moved into a long transport layer header
+    const KEY: Key = [
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+        0x11,
+    ];
+
+    const USER_DATA: [u8; 45] = [
+        0x72, 0x78, 0x56, 0x34, 0x12, 0x93, 0x15, 0x33, 0x03, 0x2A, 0x00, 0x20, 0x05, 0x59, 0x23,
+        0xC9, 0x5A, 0xAA, 0x26, 0xD1, 0xB2, 0xE7, 0x49, 0x3B, 0x01, 0x3E, 0xC4, 0xA6, 0xF6, 0xD3,
+        0x52, 0x9B, 0x52, 0x0E, 0xDF, 0xF0, 0xEA, 0x6D, 0xEF, 0xC9, 0x9D, 0x6D, 0x69, 0xEB, 0xF3,
+    ];
+
+    const MODE9_KEY: Key = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+
+    // the mode 7 and 9 messages are built with the key derivation and nonce of this module on
+    // top of the AES-CMAC and AES-GCM primitives, see test_derive_key and test_aes_gcm
+
+    // message control and message counter 0x12 of the AFL
+    const MODE7_AFL: [u8; 5] = [0x25, 0x12, 0x00, 0x00, 0x00];
+
+    const MODE7_MAC: [u8; 8] = [0x02, 0xAF, 0xBD, 0xF9, 0x45, 0x15, 0x97, 0x35];
+
+    // one encrypted block after the configuration field extension 0x10
+    const MODE7_USER_DATA: [u8; 30] = [
+        0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2B, 0x00, 0x10, 0x07, 0x10, 0xC8,
+        0x1A, 0x40, 0xE6, 0xF8, 0xB7, 0x52, 0x63, 0x86, 0xE6, 0x98, 0xC3, 0x14, 0x0C, 0xD8, 0x76,
+    ];
+
+    // message control and message counter 0x13 of the AFL
+    const MODE9_AFL: [u8; 5] = [0x26, 0x13, 0x00, 0x00, 0x00];
+
+    const MODE9_TAG: [u8; 12] = [
+        0x24, 0x99, 0xF5, 0x8F, 0xA3, 0xBE, 0xFB, 0xF9, 0xD0, 0x32, 0xA1, 0x55,
+    ];
+
+    const MODE9_USER_DATA: [u8; 22] = [
+        0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2C, 0x00, 0x00, 0x09, 0xFB, 0xD0,
+        0x35, 0xF1, 0x9A, 0xF2, 0x6F, 0x60, 0x7E,
+    ];
+
+    #[test]
+    fn test_mode5_iv() {
+        let address = SecondaryAddress::new(0x12345678, 0x1593, 0x33, 0x03);
+        assert_eq!(
+            mode5_iv(&address, 0x2A),
+            [
+                0x93, 0x15, 0x78, 0x56, 0x34, 0x12, 0x33, 0x03, 0x2A, 0x2A, 0x2A, 0x2A, 0x2A, 0x2A,
+                0x2A, 0x2A
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decrypt_mode5() {
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(USER_DATA.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        let mut buffer = [0u8; 64];
+        let mut records = decrypt_records(
+            &fixed_data_header,
+            variable_data_block,
+            &KEY,
+            None,
+            &mut buffer,
+        )
+        .unwrap()
+        .flatten();
+        // volume 28504.27 m³, date and time 2008-05-31 23:50 and no errors
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(2850427.0))
+        );
+        assert!(matches!(
+            records.next().unwrap().data.value,
+            Some(DataType::DateTime(..))
+        ));
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(0.0))
+        );
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_decrypt_errors() {
+        let address = SecondaryAddress::new(0x12345678, 0x1593, 0x33, 0x03);
+        let data = &USER_DATA[13..];
+        let mut buffer = [0u8; 64];
+
+        let mut key = KEY;
+        key[0] = 0xFF;
+        assert_eq!(
+            decrypt(
+                &address,
+                0x2A,
+                &ConfigurationField::from(0x0520),
+                data,
+                &key,
+                None,
+                &mut buffer
+            ),
+            Err(DecryptionError::WrongKey)
+        );
+        assert_eq!(
+            decrypt(
+                &SecondaryAddress::new(0x12345678, 0x1594, 0x33, 0x03),
+                0x2A,
+                &ConfigurationField::from(0x0520),
+                data,
+                &KEY,
+                None,
+                &mut buffer
+            ),
+            Err(DecryptionError::WrongKey)
+        );
+        assert_eq!(
+            decrypt(
+                &address,
+                0x2A,
+                &ConfigurationField::from(0x0530),
+                data,
+                &KEY,
+                None,
+                &mut buffer
+            ),
+            Err(DecryptionError::InvalidLength { length: 48 })
+        );
+        assert_eq!(
+            decrypt(
+                &address,
+                0x2A,
+                &ConfigurationField::from(0x0B20),
+                data,
+                &KEY,
+                None,
+                &mut buffer
+            ),
+            Err(DecryptionError::UnsupportedMode {
+                mode: SecurityMode::Reserved(11)
+            })
+        );
+        assert_eq!(
+            decrypt(
+                &address,
+                0x2A,
+                &ConfigurationField::from(0x0520),
+                data,
+                &KEY,
+                None,
+                &mut buffer[..16]
+            ),
+            Err(DecryptionError::BufferTooSmall { required: 32 })
+        );
+        assert_eq!(
+            decrypt(
+                &address,
+                0x2A,
+                &ConfigurationField::from(0x0000),
+                data,
+                &KEY,
+                None,
+                &mut buffer
+            ),
+            Ok(32)
+        );
+        assert_eq!(&buffer[..32], data);
+    }
+
+    #[test]
+    fn test_derive_key() {
+        // AES-CMAC example 2 of RFC 4493, section 4
+        let key = [
+            0x2B, 0x7E, 0x15, 0x16, 0x28, 0xAE, 0xD2, 0xA6, 0xAB, 0xF7, 0x15, 0x88, 0x09, 0xCF,
+            0x4F, 0x3C,
+        ];
+        let message = [
+            0x6B, 0xC1, 0xBE, 0xE2, 0x2E, 0x40, 0x9F, 0x96, 0xE9, 0x3D, 0x7E, 0x11, 0x73, 0x93,
+            0x17, 0x2A,
+        ];
+        assert_eq!(
+            cmac(&key, &[&message]).finalize().into_bytes().as_slice(),
+            [
+                0x07, 0x0A, 0x16, 0xB4, 0x6B, 0x4D, 0x41, 0x44, 0xF7, 0x9B, 0xDD, 0x9D, 0xD0, 0x4A,
+                0x28, 0x7C
+            ]
+        );
+        // the input block is D, the message counter and the identification number little
+        // endian, padded with 0x07
+        for (constant, input) in [
+            (
+                DERIVATION_ENCRYPTION,
+                [
+                    0x00, 0x12, 0x00, 0x00, 0x00, 0x78, 0x56, 0x34, 0x12, 0x07, 0x07, 0x07, 0x07,
+                    0x07, 0x07, 0x07,
+                ],
+            ),
+            (
+                DERIVATION_MAC,
+                [
+                    0x01, 0x12, 0x00, 0x00, 0x00, 0x78, 0x56, 0x34, 0x12, 0x07, 0x07, 0x07, 0x07,
+                    0x07, 0x07, 0x07,
+                ],
+            ),
+        ] {
+            assert_eq!(
+                derive_key(&KEY, constant, 0x12, 0x12345678).as_slice(),
+                cmac(&KEY, &[&input]).finalize().into_bytes().as_slice()
+            );
+        }
+        assert_eq!(
+            derive_key(&KEY, DERIVATION_ENCRYPTION, 0x12, 0x12345678),
+            [
+                0xB9, 0x16, 0xA6, 0x63, 0x56, 0x6D, 0x24, 0x1A, 0xD1, 0xAF, 0x69, 0x5A, 0xF3, 0x76,
+                0x52, 0xEB
+            ]
+        );
+        assert_eq!(
+            derive_key(&KEY, DERIVATION_MAC, 0x12, 0x12345678),
+            [
+                0xE5, 0x89, 0x59, 0x38, 0x2B, 0x92, 0x74, 0x3B, 0x03, 0xC1, 0x7C, 0x2F, 0xCC, 0xFE,
+                0x98, 0xA5
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decrypt_mode7() {
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(MODE7_USER_DATA.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        let authentication = Authentication {
+            fields: &MODE7_AFL,
+            message_counter: 0x12,
+            mac: &MODE7_MAC,
+            message: &MODE7_USER_DATA,
+        };
+        let mut buffer = [0u8; 64];
+        let mut records = decrypt_records(
+            &fixed_data_header,
+            variable_data_block,
+            &KEY,
+            Some(&authentication),
+            &mut buffer,
+        )
+        .unwrap()
+        .flatten();
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(5.0))
+        );
+        assert!(records.next().is_none());
+
+        assert_eq!(
+            decrypt_records(
+                &fixed_data_header,
+                variable_data_block,
+                &MODE9_KEY,
+                Some(&authentication),
+                &mut buffer,
+            )
+            .err(),
+            Some(DecryptionError::MacMismatch)
+        );
+        assert_eq!(
+            decrypt_records(
+                &fixed_data_header,
+                variable_data_block,
+                &KEY,
+                None,
+                &mut buffer,
+            )
+            .err(),
+            Some(DecryptionError::MissingAuthentication)
+        );
+
+        // the access number is not encrypted but covered by the MAC
+        let mut altered = MODE7_USER_DATA;
+        altered[9] = 0x2C;
+        assert_eq!(
+            decrypt_records(
+                &fixed_data_header,
+                variable_data_block,
+                &KEY,
+                Some(&Authentication {
+                    message: &altered,
+                    ..authentication
+                }),
+                &mut buffer,
+            )
+            .err(),
+            Some(DecryptionError::MacMismatch)
+        );
+
+        // an altered ciphertext fails authentication before it is decrypted
+        let mut altered = MODE7_USER_DATA;
+        altered[20] ^= 0x01;
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(altered.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert_eq!(
+            decrypt_records(
+                &fixed_data_header,
+                variable_data_block,
+                &KEY,
+                Some(&Authentication {
+                    message: &altered,
+                    ..authentication
+                }),
+                &mut buffer,
+            )
+            .err(),
+            Some(DecryptionError::MacMismatch)
+        );
+    }
+
+    #[test]
+    fn test_aes_gcm() {
+        // test case 4 of "The Galois/Counter Mode of Operation" (McGrew, Viega) with the tag
+        // truncated to 12 bytes
+        let key = [
+            0xFE, 0xFF, 0xE9, 0x92, 0x86, 0x65, 0x73, 0x1C, 0x6D, 0x6A, 0x8F, 0x94, 0x67, 0x30,
+            0x83, 0x08,
+        ];
+        let nonce = [
+            0xCA, 0xFE, 0xBA, 0xBE, 0xFA, 0xCE, 0xDB, 0xAD, 0xDE, 0xCA, 0xF8, 0x88,
+        ];
+        let aad = [
+            0xFE, 0xED, 0xFA, 0xCE, 0xDE, 0xAD, 0xBE, 0xEF, 0xFE, 0xED, 0xFA, 0xCE, 0xDE, 0xAD,
+            0xBE, 0xEF, 0xAB, 0xAD, 0xDA, 0xD2,
+        ];
+        let mut data = [
+            0x42, 0x83, 0x1E, 0xC2, 0x21, 0x77, 0x74, 0x24, 0x4B, 0x72, 0x21, 0xB7, 0x84, 0xD0,
+            0xD4, 0x9C, 0xE3, 0xAA, 0x21, 0x2F, 0x2C, 0x02, 0xA4, 0xE0, 0x35, 0xC1, 0x7E, 0x23,
+            0x29, 0xAC, 0xA1, 0x2E, 0x21, 0xD5, 0x14, 0xB2, 0x54, 0x66, 0x93, 0x1C, 0x7D, 0x8F,
+            0x6A, 0x5A, 0xAC, 0x84, 0xAA, 0x05, 0x1B, 0xA3, 0x0B, 0x39, 0x6A, 0x0A, 0xAC, 0x97,
+            0x3D, 0x58, 0xE0, 0x91,
+        ];
+        let tag = [
+            0x5B, 0xC9, 0x4F, 0xBC, 0x32, 0x21, 0xA5, 0xDB, 0x94, 0xFA, 0xE9, 0x5A,
+        ];
+        Aes128Gcm::new(&key.into())
+            .decrypt_in_place_detached(
+                Nonce::from_slice(&nonce),
+                &aad,
+                &mut data,
+                Tag::<U12>::from_slice(&tag),
+            )
+            .unwrap();
+        assert_eq!(
+            data,
+            [
+                0xD9, 0x31, 0x32, 0x25, 0xF8, 0x84, 0x06, 0xE5, 0xA5, 0x59, 0x09, 0xC5, 0xAF, 0xF5,
+                0x26, 0x9A, 0x86, 0xA7, 0xA9, 0x53, 0x15, 0x34, 0xF7, 0xDA, 0x2E, 0x4C, 0x30, 0x3D,
+                0x8A, 0x31, 0x8A, 0x72, 0x1C, 0x3C, 0x0C, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2F, 0xCF,
+                0x0E, 0x24, 0x49, 0xA6, 0xB5, 0x25, 0xB1, 0x6A, 0xED, 0xF5, 0xAA, 0x0D, 0xE6, 0x57,
+                0xBA, 0x63, 0x7B, 0x39
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decrypt_mode9() {
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(MODE9_USER_DATA.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert_eq!(
+            fixed_data_header.configuration_field.security_mode,
+            SecurityMode::AesGcm
+        );
+        let authentication = Authentication {
+            fields: &MODE9_AFL,
+            message_counter: 0x13,
+            mac: &MODE9_TAG,
+            message: &MODE9_USER_DATA,
+        };
+        let mut buffer = [0u8; 64];
+        let mut records = decrypt_records(
+            &fixed_data_header,
+            variable_data_block,
+            &MODE9_KEY,
+            Some(&authentication),
+            &mut buffer,
+        )
+        .unwrap()
+        .flatten();
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(5.0))
+        );
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(4660.0))
+        );
+        assert!(records.next().is_none());
+
+        assert_eq!(
+            decrypt_records(
+                &fixed_data_header,
+                variable_data_block,
+                &KEY,
+                Some(&authentication),
+                &mut buffer,
+            )
+            .err(),
+            Some(DecryptionError::MacMismatch)
+        );
+
+        let mut altered = MODE9_USER_DATA;
+        altered[10] = 0x04;
+        let authentication = Authentication {
+            message: &altered,
+            ..authentication
+        };
+        assert_eq!(
+            decrypt_records(
+                &fixed_data_header,
+                variable_data_block,
+                &MODE9_KEY,
+                Some(&authentication),
+                &mut buffer,
+            )
+            .err(),
+            Some(DecryptionError::MacMismatch)
+        );
+    }
+
+    #[test]
+    fn test_decrypt_mode9_without_check_bytes() {
+        // the plaintext starts with the first record instead of 0x2F 0x2F
+        let data = [
+            0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2D, 0x00, 0x00, 0x09, 0xD8,
+            0x3A, 0xE5, 0x88, 0x52, 0x8B, 0x71,
+        ];
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        let authentication = Authentication {
+            fields: &[0x26, 0x14, 0x00, 0x00, 0x00],
+            message_counter: 0x14,
+            mac: &[
+                0xF2, 0x53, 0x05, 0xD8, 0x0C, 0xE6, 0xA4, 0xB8, 0x28, 0xF2, 0xFD, 0xC9,
+            ],
+            message: &data,
+        };
+        let mut buffer = [0u8; 64];
+        let mut records = decrypt_records(
+            &fixed_data_header,
+            variable_data_block,
+            &MODE9_KEY,
+            Some(&authentication),
+            &mut buffer,
+        )
+        .unwrap()
+        .flatten();
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(5.0))
+        );
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(4660.0))
+        );
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_decrypt_mode7_behind_afl() {
+        use crate::afl::AuthenticationFragmentationLayer;
+
+        let mut data = [0u8; 47];
+        data[..17].copy_from_slice(&[
+            0x90, 0x0F, 0x00, 0x2C, 0x25, 0x12, 0x00, 0x00, 0x00, 0x02, 0xAF, 0xBD, 0xF9, 0x45,
+            0x15, 0x97, 0x35,
+        ]);
+        data[17..].copy_from_slice(&MODE7_USER_DATA);
+        let (Some(afl), message) = AuthenticationFragmentationLayer::parse(&data).unwrap() else {
+            panic!("expected an AFL");
+        };
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        let mut buffer = [0u8; 64];
+        let mut records = decrypt_records(
+            &fixed_data_header,
+            variable_data_block,
+            &KEY,
+            afl.authentication(message).as_ref(),
+            &mut buffer,
+        )
+        .unwrap()
+        .flatten();
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(5.0))
+        );
+    }
+}
@@ -0,0 +1,428 @@
+//! Sans-IO emulation of a wired M-Bus slave, e.g. a fake meter for integration tests
+//!
+//! The slave answers the frames of a master the way EN 13757-2/3 describes it: SND_NKE and
+//! SND_UD are acknowledged with `E5`, REQ_UD2 is answered with a RSP_UD long frame carrying
+//! the fixed data header and the configured data records.
+use arrayvec::ArrayVec;
+
+use crate::frames::{
+    Address, Frame, FrameError, Function, MAX_FRAME_DATA_LENGTH, MAX_FRAME_LENGTH,
+};
+use crate::user_data::data_information::DataType;
+use crate::user_data::data_record::DataRecord;
+use crate::user_data::value_information::ValueLabel;
+use crate::user_data::variable_user_data::DataRecordError;
+use crate::user_data::{DataRecords, FixedDataHeader, SecondaryAddress, UserDataBlock};
+
+const ACKNOWLEDGE: u8 = 0xE5;
+const RESPONSE_WITH_VARIABLE_DATA_STRUCTURE: u8 = 0x72;
+const FIXED_DATA_HEADER_LENGTH: usize = 12;
+/// Space left for data records in a RSP_UD after the CI field and the fixed data header
+pub const MAX_RECORDS_LENGTH: usize = MAX_FRAME_DATA_LENGTH - 1 - FIXED_DATA_HEADER_LENGTH;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SlaveError {
+    InvalidRecord(DataRecordError),
+    /// The record parses but its length does not match the given bytes
+    RecordLengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    RecordsTooLong {
+        length: usize,
+    },
+}
+
+impl From<DataRecordError> for SlaveError {
+    fn from(error: DataRecordError) -> Self {
+        Self::InvalidRecord(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for SlaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlaveError::InvalidRecord(error) => write!(f, "Invalid data record: {:?}", error),
+            SlaveError::RecordLengthMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Record length mismatch: expected {}, actual {}",
+                    expected, actual
+                )
+            }
+            SlaveError::RecordsTooLong { length } => write!(f, "Records too long: {}", length),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SlaveError {}
+
+/// A meter with a primary and a secondary address answering requests of a master
+#[derive(Debug)]
+pub struct Slave {
+    primary_address: u8,
+    fixed_data_header: FixedDataHeader,
+    records: ArrayVec<u8, MAX_RECORDS_LENGTH>,
+    selected: bool,
+    /// FCB of the last REQ_UD2, `None` after SND_NKE
+    last_fcb: Option<bool>,
+    response: [u8; MAX_FRAME_LENGTH],
+    response_length: usize,
+}
+
+impl Slave {
+    #[must_use]
+    pub fn new(primary_address: u8, fixed_data_header: FixedDataHeader) -> Self {
+        Self {
+            primary_address,
+            fixed_data_header,
+            records: ArrayVec::new(),
+            selected: false,
+            last_fcb: None,
+            response: [0; MAX_FRAME_LENGTH],
+            response_length: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn primary_address(&self) -> u8 {
+        self.primary_address
+    }
+
+    #[must_use]
+    pub fn secondary_address(&self) -> SecondaryAddress {
+        SecondaryAddress::from(&self.fixed_data_header)
+    }
+
+    #[must_use]
+    pub const fn fixed_data_header(&self) -> &FixedDataHeader {
+        &self.fixed_data_header
+    }
+
+    #[must_use]
+    pub const fn is_selected(&self) -> bool {
+        self.selected
+    }
+
+    #[must_use]
+    pub fn records(&self) -> DataRecords<'_> {
+        DataRecords::from(self.records.as_slice())
+    }
+
+    /// Appends an encoded data record to the readout, the record is validated by parsing it
+    pub fn push_record(&mut self, record: &[u8]) -> Result<(), SlaveError> {
+        let parsed = DataRecord::try_from(record)?;
+        if parsed.get_size() != record.len() {
+            return Err(SlaveError::RecordLengthMismatch {
+                expected: parsed.get_size(),
+                actual: record.len(),
+            });
+        }
+        self.records
+            .try_extend_from_slice(record)
+            .map_err(|_| SlaveError::RecordsTooLong {
+                length: self.records.len() + record.len(),
+            })
+    }
+
+    pub fn clear_records(&mut self) {
+        self.records.clear();
+    }
+
+    /// Processes a frame received from the master and returns the encoded answer, if any
+    pub fn handle(&mut self, request: &Frame<'_>) -> Option<&[u8]> {
+        match request {
+            Frame::ShortFrame { function, address } if self.is_addressed(address) => {
+                match function {
+                    Function::SndNk => {
+                        // SND_NKE to the secondary address ends the selection
+                        if *address == Address::Secondary {
+                            self.selected = false;
+                        }
+                        self.last_fcb = None;
+                        self.acknowledge(address)
+                    }
+                    Function::ReqUd2 { fcb } => {
+                        if self.last_fcb != Some(*fcb) || self.response_length == 0 {
+                            self.last_fcb = Some(*fcb);
+                            self.encode_user_data().ok()?;
+                        }
+                        // the master did not toggle the FCB, so it missed the last answer
+                        self.reply(address)
+                    }
+                    Function::ReqUd1 { .. } => self.acknowledge(address),
+                    Function::SndUd { .. } | Function::RspUd { .. } => None,
+                }
+            }
+            Frame::ControlFrame {
+                function: Function::SndUd { .. },
+                address,
+                data,
+            } => match UserDataBlock::try_from(*data) {
+                Ok(UserDataBlock::SelectSlave { secondary_address })
+                    if *address == Address::Secondary =>
+                {
+                    // slaves not matching the selection are deselected and stay silent
+                    self.selected = secondary_address.matches(&self.secondary_address());
+                    if !self.selected {
+                        return None;
+                    }
+                    self.acknowledge(address)
+                }
+                Ok(UserDataBlock::SendData {
+                    variable_data_block,
+                }) if self.is_addressed(address) => {
+                    self.write_records(variable_data_block);
+                    self.acknowledge(address)
+                }
+                _ if self.is_addressed(address) => self.acknowledge(address),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn is_addressed(&self, address: &Address) -> bool {
+        match address {
+            Address::Secondary => self.selected,
+            Address::Broadcast { .. } => true,
+            _ => u8::from(address) == self.primary_address,
+        }
+    }
+
+    /// Applies the records written by the master, currently only a new primary address
+    fn write_records(&mut self, data: &[u8]) {
+        for record in DataRecords::from(data).flatten() {
+            let is_address = record
+                .data_record_header
+                .processed_data_record_header
+                .value_information
+                .as_ref()
+                .is_some_and(|value_information| {
+                    value_information.labels.contains(&ValueLabel::Address)
+                });
+            if let (true, Some(DataType::Number(address))) = (is_address, &record.data.value) {
+                if (0.0..=250.0).contains(address) {
+                    self.primary_address = *address as u8;
+                }
+            }
+        }
+    }
+
+    fn acknowledge(&mut self, address: &Address) -> Option<&[u8]> {
+        if *address
+            == (Address::Broadcast {
+                reply_required: false,
+            })
+        {
+            return None;
+        }
+        Some(&[ACKNOWLEDGE])
+    }
+
+    fn reply(&self, address: &Address) -> Option<&[u8]> {
+        if *address
+            == (Address::Broadcast {
+                reply_required: false,
+            })
+        {
+            return None;
+        }
+        self.response.get(..self.response_length)
+    }
+
+    fn encode_user_data(&mut self) -> Result<(), FrameError> {
+        let header = &mut self.fixed_data_header;
+        if self.response_length != 0 {
+            header.access_number = header.access_number.wrapping_add(1);
+        }
+        let mut data = ArrayVec::<u8, MAX_FRAME_DATA_LENGTH>::new();
+        data.push(RESPONSE_WITH_VARIABLE_DATA_STRUCTURE);
+        let _ = data.try_extend_from_slice(&SecondaryAddress::from(&*header).to_bytes());
+        let _ = data.try_extend_from_slice(&[header.access_number, header.status.bits()]);
+        let _ = data.try_extend_from_slice(&u16::from(&header.configuration_field).to_le_bytes());
+        let _ = data.try_extend_from_slice(&self.records);
+
+        self.response_length = Frame::LongFrame {
+            function: Function::RspUd {
+                acd: false,
+                dfc: false,
+            },
+            address: Address::from(self.primary_address),
+            data: &data,
+        }
+        .encode_into(&mut self.response)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::master::{Master, Outcome, Response};
+    use crate::user_data::{
+        ConfigurationField, IdentificationNumber, ManufacturerCode, Medium, StatusField,
+    };
+    use crate::MbusData;
+
+    fn slave() -> Slave {
+        let mut slave = Slave::new(
+            5,
+            FixedDataHeader {
+                identification_number: IdentificationNumber { number: 12345678 },
+                manufacturer: ManufacturerCode::from_id(0x1EE6),
+                manufacturer_id: 0x1EE6,
+                version: 0x01,
+                medium: Medium::Water,
+                medium_byte: 0x07,
+                access_number: 0,
+                status: StatusField::from_bits_truncate(0x00),
+                configuration_field: ConfigurationField::from(0),
+                lsb_order: false,
+            },
+        );
+        // volume 12.565 m³ and the fabrication number
+        slave
+            .push_record(&[0x0C, 0x13, 0x65, 0x25, 0x01, 0x00])
+            .unwrap();
+        slave
+            .push_record(&[0x0C, 0x78, 0x78, 0x56, 0x34, 0x12])
+            .unwrap();
+        slave
+    }
+
+    #[test]
+    fn test_push_record() {
+        let mut slave = slave();
+        assert_eq!(slave.records().count(), 2);
+        assert_eq!(
+            slave.push_record(&[0x0C, 0x13, 0x65]),
+            Err(SlaveError::InvalidRecord(DataRecordError::InsufficientData))
+        );
+        assert_eq!(
+            slave.push_record(&[0x01, 0x13, 0x65, 0x00]),
+            Err(SlaveError::RecordLengthMismatch {
+                expected: 3,
+                actual: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_readout_round_trip() {
+        let mut slave = slave();
+        let mut master = Master::new(Address::Primary(5));
+        master.read_user_data().unwrap();
+
+        let mut readout = None;
+        while let Some(request) = master.request() {
+            let response = slave.handle(&request).map(Frame::try_from);
+            let response = response.map_or(Response::Timeout, Response::from);
+            if let Ok(Outcome::UserData { data, .. }) = master.handle(response) {
+                readout = Some(data.len());
+            }
+        }
+        assert!(readout.is_some());
+
+        let response = slave.response.get(..slave.response_length).unwrap();
+        let parsed = MbusData::try_from(response).unwrap();
+        let Some(UserDataBlock::VariableDataStructure {
+            fixed_data_header, ..
+        }) = &parsed.user_data
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert_eq!(fixed_data_header, slave.fixed_data_header());
+        let mut records = parsed.data_records.unwrap().flatten();
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(12565.0))
+        );
+        assert_eq!(
+            records.next().unwrap().data.value,
+            Some(DataType::Number(12345678.0))
+        );
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_fcb_repeats_last_response() {
+        let mut slave = slave();
+        let request = |fcb| Frame::ShortFrame {
+            function: Function::ReqUd2 { fcb },
+            address: Address::Primary(5),
+        };
+        let access_number = |response: &[u8]| response[15];
+
+        assert_eq!(
+            slave.handle(&Frame::ShortFrame {
+                function: Function::SndNk,
+                address: Address::Primary(5)
+            }),
+            Some([0xE5].as_slice())
+        );
+        let first = access_number(slave.handle(&request(true)).unwrap());
+        let repeated = access_number(slave.handle(&request(true)).unwrap());
+        let next = access_number(slave.handle(&request(false)).unwrap());
+        assert_eq!(first, repeated);
+        assert_eq!(next, first + 1);
+
+        // other addresses are ignored
+        assert_eq!(
+            slave.handle(&Frame::ShortFrame {
+                function: Function::ReqUd2 { fcb: true },
+                address: Address::Primary(6)
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_secondary_selection() {
+        let mut slave = slave();
+        let mut select = |pattern: &str| {
+            let mut data = [0x52; 9];
+            data[1..].copy_from_slice(&pattern.parse::<SecondaryAddress>().unwrap().to_bytes());
+            slave
+                .handle(&Frame::ControlFrame {
+                    function: Function::SndUd { fcb: false },
+                    address: Address::Secondary,
+                    data: &data,
+                })
+                .is_some()
+        };
+        assert!(select("1234****-GWF-**-07"));
+        assert!(!select("1234****-GWF-**-04"));
+        assert!(select("12345678-***-**-**"));
+        assert!(slave.is_selected());
+
+        let read = Frame::ShortFrame {
+            function: Function::ReqUd2 { fcb: true },
+            address: Address::Secondary,
+        };
+        assert!(slave.handle(&read).is_some());
+
+        slave.handle(&Frame::ShortFrame {
+            function: Function::SndNk,
+            address: Address::Secondary,
+        });
+        assert!(!slave.is_selected());
+        assert_eq!(slave.handle(&read), None);
+    }
+
+    #[test]
+    fn test_set_primary_address() {
+        let mut slave = slave();
+        let frame: &[u8] = &[
+            0x68, 0x06, 0x06, 0x68, 0x53, 0x05, 0x51, 0x01, 0x7A, 0x08, 0x2C, 0x16,
+        ];
+        assert_eq!(
+            slave.handle(&Frame::try_from(frame).unwrap()),
+            Some([0xE5].as_slice())
+        );
+        assert_eq!(slave.primary_address(), 8);
+    }
+}
@@ -0,0 +1,412 @@
+//! Format frames (CI 0x69) and compact frames (CI 0x79) of EN 13757-3
+//! A compact frame only carries the data of the records and the signature of their format,
+//! the DIF/VIF headers are taken from a format frame or a full frame received earlier.
+use arrayvec::ArrayVec;
+
+use super::data_record::{DataRecord, DataRecordHeader};
+use super::DataRecords;
+use crate::wmbus::crc16;
+
+/// The length of the headers is sent in a single byte
+pub const MAX_FORMAT_LENGTH: usize = 255;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FormatError {
+    FormatTooLong {
+        length: usize,
+    },
+    /// A header or the data belonging to it could not be parsed
+    InvalidRecord {
+        offset: usize,
+    },
+    /// The format signature does not match the headers of a format frame
+    SignatureMismatch {
+        expected: u16,
+        actual: u16,
+    },
+    /// No format with this signature was received yet
+    UnknownFormat {
+        signature: u16,
+    },
+    /// The data of a compact frame does not fit to the format
+    DataLengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// The expanded records do not match the CRC of the full frame
+    DataCrcMismatch {
+        expected: u16,
+        actual: u16,
+    },
+    BufferTooSmall {
+        required: usize,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatError::FormatTooLong { length } => {
+                write!(f, "Format too long: {} bytes", length)
+            }
+            FormatError::InvalidRecord { offset } => {
+                write!(f, "Invalid record at offset {}", offset)
+            }
+            FormatError::SignatureMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Format signature mismatch: expected {:#06X}, actual {:#06X}",
+                    expected, actual
+                )
+            }
+            FormatError::UnknownFormat { signature } => {
+                write!(f, "Unknown format signature: {:#06X}", signature)
+            }
+            FormatError::DataLengthMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Data length mismatch: expected {}, actual {}",
+                    expected, actual
+                )
+            }
+            FormatError::DataCrcMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Data CRC mismatch: expected {:#06X}, actual {:#06X}",
+                    expected, actual
+                )
+            }
+            FormatError::BufferTooSmall { required } => {
+                write!(f, "Buffer too small, required: {}", required)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FormatError {}
+
+/// Format signature of EN 13757-3, the CRC over the DIF/VIF headers of all records
+#[must_use]
+pub fn format_signature(headers: &[u8]) -> u16 {
+    crc16(headers)
+}
+
+/// The DIF/VIF headers of the records of a full frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Format {
+    signature: u16,
+    headers: ArrayVec<u8, MAX_FORMAT_LENGTH>,
+}
+
+impl Format {
+    pub fn new(headers: &[u8]) -> Result<Self, FormatError> {
+        let headers = ArrayVec::try_from(headers).map_err(|_| FormatError::FormatTooLong {
+            length: headers.len(),
+        })?;
+        Ok(Self {
+            signature: format_signature(&headers),
+            headers,
+        })
+    }
+
+    /// Takes the headers of a format frame and checks them against its signature
+    pub fn from_format_frame(format_signature: u16, headers: &[u8]) -> Result<Self, FormatError> {
+        let format = Self::new(headers)?;
+        if format.signature != format_signature {
+            return Err(FormatError::SignatureMismatch {
+                expected: format_signature,
+                actual: format.signature,
+            });
+        }
+        Ok(format)
+    }
+
+    #[must_use]
+    pub const fn signature(&self) -> u16 {
+        self.signature
+    }
+
+    #[must_use]
+    pub fn headers(&self) -> &[u8] {
+        &self.headers
+    }
+
+    /// Puts the headers in front of the data of a compact frame, returns the length of the
+    /// records written to the buffer
+    pub fn expand(
+        &self,
+        data: &[u8],
+        data_crc: u16,
+        buffer: &mut [u8],
+    ) -> Result<usize, FormatError> {
+        let mut header_offset = 0;
+        let mut data_offset = 0;
+        let mut length = 0;
+        while let Some(headers) = self.headers.get(header_offset..).filter(|h| !h.is_empty()) {
+            let invalid_record = FormatError::InvalidRecord {
+                offset: header_offset,
+            };
+            let header_size = DataRecordHeader::try_from(headers)
+                .map_err(|_| invalid_record)?
+                .get_size();
+            let header = headers.get(..header_size).ok_or(invalid_record)?;
+            let remaining = data.get(data_offset..).unwrap_or_default();
+
+            // the parser determines the length of the data, including variable length data
+            let required = length + header_size + remaining.len();
+            let record = buffer
+                .get_mut(length..required)
+                .ok_or(FormatError::BufferTooSmall { required })?;
+            let (record_header, record_data) = record.split_at_mut(header_size);
+            record_header.copy_from_slice(header);
+            record_data.copy_from_slice(remaining);
+            let record_size = DataRecord::try_from(&*record)
+                .map_err(|_| invalid_record)?
+                .get_size();
+            if record_size > record.len() {
+                return Err(FormatError::DataLengthMismatch {
+                    expected: data_offset + record_size - header_size,
+                    actual: data.len(),
+                });
+            }
+
+            header_offset += header_size;
+            data_offset += record_size - header_size;
+            length += record_size;
+        }
+        if data_offset != data.len() {
+            return Err(FormatError::DataLengthMismatch {
+                expected: data_offset,
+                actual: data.len(),
+            });
+        }
+
+        let records = buffer.get(..length).unwrap_or_default();
+        let actual = crc16(records);
+        if actual != data_crc {
+            return Err(FormatError::DataCrcMismatch {
+                expected: data_crc,
+                actual,
+            });
+        }
+        Ok(length)
+    }
+}
+
+/// Collects the headers of the records of a full frame, fill bytes and manufacturer
+/// specific data are not part of the format
+impl TryFrom<&DataRecords<'_>> for Format {
+    type Error = FormatError;
+
+    fn try_from(records: &DataRecords<'_>) -> Result<Self, Self::Error> {
+        let data = records.record_data();
+        let mut headers = ArrayVec::<u8, MAX_FORMAT_LENGTH>::new();
+        let mut offset = 0;
+        while let Some(record) = data.get(offset..).filter(|r| !r.is_empty()) {
+            match record.first() {
+                Some(0x2F) => {
+                    offset += 1;
+                    continue;
+                }
+                Some(0x0F | 0x1F) | None => break,
+                Some(_) => {}
+            }
+            let record =
+                DataRecord::try_from(record).map_err(|_| FormatError::InvalidRecord { offset })?;
+            let header_size = record.data_record_header.get_size();
+            let header = data
+                .get(offset..offset + header_size)
+                .ok_or(FormatError::InvalidRecord { offset })?;
+            headers
+                .try_extend_from_slice(header)
+                .map_err(|_| FormatError::FormatTooLong {
+                    length: headers.len() + header_size,
+                })?;
+            offset += record.get_size();
+        }
+        Ok(Self {
+            signature: format_signature(&headers),
+            headers,
+        })
+    }
+}
+
+/// Formats received so far, the oldest one is replaced once all `N` entries are used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatCache<const N: usize> {
+    formats: ArrayVec<Format, N>,
+}
+
+impl<const N: usize> Default for FormatCache<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> FormatCache<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            formats: ArrayVec::new_const(),
+        }
+    }
+
+    pub fn insert(&mut self, format: Format) {
+        if let Some(position) = self
+            .formats
+            .iter()
+            .position(|cached| cached.signature == format.signature)
+        {
+            self.formats.remove(position);
+        } else if self.formats.is_full() && !self.formats.is_empty() {
+            self.formats.remove(0);
+        }
+        // only fails for a cache without entries
+        let _ = self.formats.try_push(format);
+    }
+
+    #[must_use]
+    pub fn get(&self, signature: u16) -> Option<&Format> {
+        self.formats
+            .iter()
+            .find(|format| format.signature == signature)
+    }
+
+    /// Restores the records of a compact frame with a cached format
+    pub fn decode_compact_frame<'b>(
+        &self,
+        format_signature: u16,
+        data_crc: u16,
+        data: &[u8],
+        buffer: &'b mut [u8],
+    ) -> Result<DataRecords<'b>, FormatError> {
+        let format = self
+            .get(format_signature)
+            .ok_or(FormatError::UnknownFormat {
+                signature: format_signature,
+            })?;
+        let length = format.expand(data, data_crc, buffer)?;
+        Ok(DataRecords::new(
+            buffer.get(..length).unwrap_or_default(),
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Volume 0x12345678 l, flow temperature 0x1234, fill byte, a date and a string
+    const FULL_RECORDS: [u8; 22] = [
+        0x04, 0x13, 0x78, 0x56, 0x34, 0x12, 0x02, 0x5A, 0x34, 0x12, 0x2F, 0x02, 0x6C, 0x21, 0x1C,
+        0x0D, 0xFD, 0x0C, 0x02, 0x41, 0x42, 0x2F,
+    ];
+    const HEADERS: [u8; 9] = [0x04, 0x13, 0x02, 0x5A, 0x02, 0x6C, 0x0D, 0xFD, 0x0C];
+    const COMPACT_DATA: [u8; 11] = [
+        0x78, 0x56, 0x34, 0x12, 0x34, 0x12, 0x21, 0x1C, 0x02, 0x41, 0x42,
+    ];
+
+    fn expanded_records() -> [u8; 20] {
+        [
+            0x04, 0x13, 0x78, 0x56, 0x34, 0x12, 0x02, 0x5A, 0x34, 0x12, 0x02, 0x6C, 0x21, 0x1C,
+            0x0D, 0xFD, 0x0C, 0x02, 0x41, 0x42,
+        ]
+    }
+
+    #[test]
+    fn test_format_of_full_frame() {
+        let records = DataRecords::new(&FULL_RECORDS, None);
+        let format = Format::try_from(&records);
+        assert_eq!(format.as_ref().map(Format::headers), Ok(HEADERS.as_slice()));
+        assert_eq!(
+            format.map(|format| format.signature()),
+            Ok(format_signature(&HEADERS))
+        );
+    }
+
+    #[test]
+    fn test_decode_compact_frame() {
+        let mut cache = FormatCache::<2>::new();
+        let signature = format_signature(&HEADERS);
+        let data_crc = crc16(&expanded_records());
+        let mut buffer = [0u8; 64];
+        assert_eq!(
+            cache
+                .decode_compact_frame(signature, data_crc, &COMPACT_DATA, &mut buffer)
+                .map(|_| ()),
+            Err(FormatError::UnknownFormat { signature })
+        );
+
+        let format = Format::from_format_frame(signature, &HEADERS);
+        assert!(format.is_ok());
+        if let Ok(format) = format {
+            cache.insert(format);
+        }
+        let records = cache.decode_compact_frame(signature, data_crc, &COMPACT_DATA, &mut buffer);
+        assert_eq!(
+            records.as_ref().map(DataRecords::record_data),
+            Ok(expanded_records().as_slice())
+        );
+        assert_eq!(records.map(|records| records.count()), Ok(4));
+
+        assert_eq!(
+            cache
+                .decode_compact_frame(signature, !data_crc, &COMPACT_DATA, &mut buffer)
+                .map(|_| ()),
+            Err(FormatError::DataCrcMismatch {
+                expected: !data_crc,
+                actual: data_crc,
+            })
+        );
+        let mut longer_data = [0u8; 12];
+        longer_data[..11].copy_from_slice(&COMPACT_DATA);
+        assert_eq!(
+            cache
+                .decode_compact_frame(signature, data_crc, &longer_data, &mut buffer)
+                .map(|_| ()),
+            Err(FormatError::DataLengthMismatch {
+                expected: 11,
+                actual: 12,
+            })
+        );
+        assert_eq!(
+            cache
+                .decode_compact_frame(signature, data_crc, &COMPACT_DATA, &mut buffer[..10])
+                .map(|_| ()),
+            Err(FormatError::BufferTooSmall { required: 13 })
+        );
+    }
+
+    #[test]
+    fn test_format_errors() {
+        assert_eq!(
+            Format::from_format_frame(0x0000, &HEADERS).map(|_| ()),
+            Err(FormatError::SignatureMismatch {
+                expected: 0x0000,
+                actual: format_signature(&HEADERS),
+            })
+        );
+        assert_eq!(
+            Format::new(&[0x04; 256]).map(|_| ()),
+            Err(FormatError::FormatTooLong { length: 256 })
+        );
+    }
+
+    #[test]
+    fn test_cache_replaces_oldest_format() {
+        let mut cache = FormatCache::<2>::new();
+        for headers in [[0x04, 0x13], [0x02, 0x5A], [0x02, 0x6C]] {
+            if let Ok(format) = Format::new(&headers) {
+                cache.insert(format);
+            }
+        }
+        assert!(cache.get(format_signature(&[0x04, 0x13])).is_none());
+        assert!(cache.get(format_signature(&[0x02, 0x5A])).is_some());
+        assert!(cache.get(format_signature(&[0x02, 0x6C])).is_some());
+    }
+}
@@ -0,0 +1,315 @@
+//! Configuration field of the transport layer header (EN 13757-7, OMS)
+#[cfg(feature = "std")]
+use std::fmt;
+
+/// Size of an encrypted block in the block oriented security modes
+const BLOCK_SIZE: usize = 16;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SecurityMode {
+    NoEncryption,
+    ManufacturerSpecific,
+    /// Mode 2, deprecated
+    DesCbcZeroIv,
+    /// Mode 3, deprecated
+    DesCbc,
+    /// Mode 5
+    AesCbc,
+    /// Mode 7, ephemeral key
+    AesCbcZeroIv,
+    /// Mode 8
+    AesCtrCmac,
+    /// Mode 9
+    AesGcm,
+    /// Mode 10
+    AesCcm,
+    /// Mode 13
+    Tls,
+    Reserved(u8),
+}
+
+impl SecurityMode {
+    #[must_use]
+    pub const fn from_bits(bits: u8) -> Self {
+        match bits & 0x1F {
+            0 => Self::NoEncryption,
+            1 => Self::ManufacturerSpecific,
+            2 => Self::DesCbcZeroIv,
+            3 => Self::DesCbc,
+            5 => Self::AesCbc,
+            7 => Self::AesCbcZeroIv,
+            8 => Self::AesCtrCmac,
+            9 => Self::AesGcm,
+            10 => Self::AesCcm,
+            13 => Self::Tls,
+            bits => Self::Reserved(bits),
+        }
+    }
+
+    /// Mode number as used in the configuration field
+    #[must_use]
+    pub const fn to_bits(&self) -> u8 {
+        match self {
+            Self::NoEncryption => 0,
+            Self::ManufacturerSpecific => 1,
+            Self::DesCbcZeroIv => 2,
+            Self::DesCbc => 3,
+            Self::AesCbc => 5,
+            Self::AesCbcZeroIv => 7,
+            Self::AesCtrCmac => 8,
+            Self::AesGcm => 9,
+            Self::AesCcm => 10,
+            Self::Tls => 13,
+            Self::Reserved(bits) => *bits & 0x1F,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for SecurityMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecurityMode::NoEncryption => write!(f, "No encryption"),
+            SecurityMode::ManufacturerSpecific => write!(f, "Manufacturer specific"),
+            SecurityMode::DesCbcZeroIv => write!(f, "DES-CBC, IV = 0"),
+            SecurityMode::DesCbc => write!(f, "DES-CBC"),
+            SecurityMode::AesCbc => write!(f, "AES-CBC-128"),
+            SecurityMode::AesCbcZeroIv => write!(f, "AES-CBC-128, IV = 0"),
+            SecurityMode::AesCtrCmac => write!(f, "AES-CTR-128, CMAC"),
+            SecurityMode::AesGcm => write!(f, "AES-GCM-128"),
+            SecurityMode::AesCcm => write!(f, "AES-CCM-128"),
+            SecurityMode::Tls => write!(f, "TLS"),
+            SecurityMode::Reserved(bits) => write!(f, "Reserved ({})", bits),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MessageContent {
+    /// Meter data
+    StandardData,
+    /// Parameters and other rarely changing data
+    StaticData,
+    Reserved(u8),
+}
+
+impl MessageContent {
+    const fn from_bits(bits: u8) -> Self {
+        match bits & 0x03 {
+            0 => Self::StandardData,
+            1 => Self::StaticData,
+            bits => Self::Reserved(bits),
+        }
+    }
+
+    const fn to_bits(self) -> u8 {
+        match self {
+            Self::StandardData => 0,
+            Self::StaticData => 1,
+            Self::Reserved(bits) => bits & 0x03,
+        }
+    }
+}
+
+/// Lower byte of the configuration field, its meaning depends on the security mode
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ModeConfiguration {
+    /// No encryption and the block oriented modes 5 and 7
+    Blocks {
+        /// Number of encrypted 16 byte blocks
+        encrypted_blocks: u8,
+        content: MessageContent,
+        hop_counter: u8,
+    },
+    /// Not decoded for the other modes
+    Other(u8),
+}
+
+/// Configuration field of the transport layer header, previously called signature
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConfigurationField {
+    pub bidirectional: bool,
+    pub accessibility: bool,
+    pub synchronous: bool,
+    pub security_mode: SecurityMode,
+    pub mode_configuration: ModeConfiguration,
+}
+
+impl ConfigurationField {
+    #[must_use]
+    pub const fn is_encrypted(&self) -> bool {
+        !matches!(self.security_mode, SecurityMode::NoEncryption)
+    }
+
+    /// Number of encrypted bytes at the start of a payload of the given length
+    #[must_use]
+    pub const fn encrypted_length(&self, payload_length: usize) -> usize {
+        match (self.security_mode, self.mode_configuration) {
+            (SecurityMode::NoEncryption, _) => 0,
+            (
+                _,
+                ModeConfiguration::Blocks {
+                    encrypted_blocks, ..
+                },
+            ) => encrypted_blocks as usize * BLOCK_SIZE,
+            (_, ModeConfiguration::Other(_)) => payload_length,
+        }
+    }
+}
+
+impl From<u16> for ConfigurationField {
+    fn from(value: u16) -> Self {
+        let security_mode = SecurityMode::from_bits((value >> 8) as u8);
+        let low = value as u8;
+        let mode_configuration = match security_mode {
+            SecurityMode::NoEncryption | SecurityMode::AesCbc | SecurityMode::AesCbcZeroIv => {
+                ModeConfiguration::Blocks {
+                    encrypted_blocks: low >> 4,
+                    content: MessageContent::from_bits(low >> 2),
+                    hop_counter: low & 0x03,
+                }
+            }
+            _ => ModeConfiguration::Other(low),
+        };
+        Self {
+            bidirectional: value & 0x8000 != 0,
+            accessibility: value & 0x4000 != 0,
+            synchronous: value & 0x2000 != 0,
+            security_mode,
+            mode_configuration,
+        }
+    }
+}
+
+impl From<&ConfigurationField> for u16 {
+    fn from(field: &ConfigurationField) -> Self {
+        let low = match field.mode_configuration {
+            ModeConfiguration::Blocks {
+                encrypted_blocks,
+                content,
+                hop_counter,
+            } => (encrypted_blocks << 4) | (content.to_bits() << 2) | (hop_counter & 0x03),
+            ModeConfiguration::Other(low) => low,
+        };
+        (u16::from(field.bidirectional) << 15)
+            | (u16::from(field.accessibility) << 14)
+            | (u16::from(field.synchronous) << 13)
+            | (u16::from(field.security_mode.to_bits()) << 8)
+            | u16::from(low)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ConfigurationField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06X} {}", u16::from(self), self.security_mode)?;
+        if let ModeConfiguration::Blocks {
+            encrypted_blocks, ..
+        } = self.mode_configuration
+        {
+            if self.is_encrypted() {
+                write!(f, ", {} encrypted blocks", encrypted_blocks)?;
+            }
+        }
+        if self.bidirectional {
+            write!(f, ", bidirectional")?;
+        }
+        if self.accessibility {
+            write!(f, ", accessible")?;
+        }
+        if self.synchronous {
+            write!(f, ", synchronous")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configuration_field() {
+        let field = ConfigurationField::from(0x0520);
+        assert_eq!(
+            field,
+            ConfigurationField {
+                bidirectional: false,
+                accessibility: false,
+                synchronous: false,
+                security_mode: SecurityMode::AesCbc,
+                mode_configuration: ModeConfiguration::Blocks {
+                    encrypted_blocks: 2,
+                    content: MessageContent::StandardData,
+                    hop_counter: 0,
+                },
+            }
+        );
+        assert!(field.is_encrypted());
+        assert_eq!(field.encrypted_length(40), 32);
+
+        let field = ConfigurationField::from(0x0000);
+        assert!(!field.is_encrypted());
+        assert_eq!(field.encrypted_length(40), 0);
+
+        let field = ConfigurationField::from(0xE9AB);
+        assert!(field.bidirectional && field.accessibility && field.synchronous);
+        assert_eq!(field.security_mode, SecurityMode::AesGcm);
+        assert_eq!(field.mode_configuration, ModeConfiguration::Other(0xAB));
+        assert_eq!(field.encrypted_length(40), 40);
+
+        for value in [0x0000, 0x0520, 0x0715, 0xE9AB, 0x1F00, 0x0007, 0x850C] {
+            assert_eq!(u16::from(&ConfigurationField::from(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_encrypted_records_are_not_decoded() {
+        use crate::frames::{Address, Frame, Function};
+        use crate::user_data::UserDataBlock;
+
+        let data = [
+            0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2A, 0x00, 0x10, 0x05, 0x3D,
+            0xDB, 0x07, 0x70, 0x2D, 0xEF, 0xF3, 0xF8, 0x7F, 0xB3, 0xB1, 0x12, 0xDE, 0x34, 0x3B,
+            0xDF,
+        ];
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header, ..
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert!(fixed_data_header.configuration_field.is_encrypted());
+
+        let mbus_data = crate::MbusData::from(Frame::LongFrame {
+            function: Function::RspUd {
+                acd: false,
+                dfc: false,
+            },
+            address: Address::Primary(1),
+            data: &data,
+        });
+        assert!(mbus_data.data_records.is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            ConfigurationField::from(0x8520).to_string(),
+            "0x8520 AES-CBC-128, 2 encrypted blocks, bidirectional"
+        );
+        assert_eq!(
+            ConfigurationField::from(0x0000).to_string(),
+            "0x0000 No encryption"
+        );
+    }
+}
@@ -195,7 +195,8 @@ impl TryFrom<&DataInformationBlock<'_>> for DataInformation {
                     return Err(DataInformationError::DataTooLong);
                 }
                 let dife = dife.data;
-                storage_number += u64::from(dife & 0x0f) << ((extension_index * 4) + 1);
+                // the DIF holds the least significant bit of the storage number
+                storage_number += u64::from(dife & 0x0f) << ((device_index * 4) + 1);
                 tariff |= u64::from((dife & 0x30) >> 4) << (tariff_index);
                 tariff_index += 2;
                 device |= u64::from((dife & 0x40) >> 6) << device_index;
@@ -476,8 +477,8 @@ fn bcd_to_value_internal(
     num_digits: usize,
     sign: i32,
     lsb_order: bool,
-) -> Result<Data, DataRecordError> {
-    if data.len() < (num_digits + 1) / 2 {
+) -> Result<Data<'_>, DataRecordError> {
+    if data.len() < num_digits.div_ceil(2) {
         return Err(DataRecordError::InsufficientData);
     }
 
@@ -506,7 +507,7 @@ fn bcd_to_value_internal(
 
     Ok(Data {
         value: Some(DataType::Number(signed_value)),
-        size: (num_digits + 1) / 2,
+        size: num_digits.div_ceil(2),
     })
 }
 
@@ -711,7 +712,13 @@ impl DataFieldCoding {
                     0
                 );
                 let month = parse_month!(input.get(3).ok_or(DataRecordError::InsufficientData)?);
-                let year = parse_year!(input, 0xF0, 0xE0, 0x7F);
+                // the year is split over the day and month bytes like in type G
+                let year = parse_year!(
+                    input.get(2..).ok_or(DataRecordError::InsufficientData)?,
+                    0xF0,
+                    0xE0,
+                    0x7F
+                );
 
                 Ok(Data {
                     value: Some(DataType::DateTime(day, month, year, hour, minutes)),
@@ -96,13 +96,14 @@ impl DataRecordHeader<'_> {
 
 impl<'a> TryFrom<&'a [u8]> for RawDataRecordHeader<'a> {
     type Error = DataRecordError;
-    fn try_from(data: &[u8]) -> Result<RawDataRecordHeader, DataRecordError> {
+    fn try_from(data: &[u8]) -> Result<RawDataRecordHeader<'_>, DataRecordError> {
         let difb = DataInformationBlock::try_from(data)?;
         let offset = difb.get_size();
 
         let mut vifb = None;
 
-        if difb.data_information_field.data != 0x0F {
+        // manufacturer specific data follows the DIF 0x0F and 0x1F directly
+        if !matches!(difb.data_information_field.data, 0x0F | 0x1F) {
             vifb = Some(ValueInformationBlock::try_from(
                 data.get(offset..)
                     .ok_or(DataRecordError::InsufficientData)?,
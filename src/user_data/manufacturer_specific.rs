@@ -0,0 +1,663 @@
+//! Decoding of the manufacturer specific data following the DIF 0x0F or 0x1F
+//!
+//! The layout of this data is defined by each manufacturer. Decoders are registered per
+//! manufacturer, optionally restricted to a version and medium, and turn the data into
+//! additional records.
+//!
+//! Records with manufacturer specific VIF or VIFE codes are rendered with a `ManufacturerVif`
+//! registered for the meter, which gives the unit, scale and label of the value.
+use arrayvec::ArrayVec;
+
+use super::data_information::DataType;
+use super::data_record::DataRecord;
+use super::value_information::{Unit, UnitName, ValueInformation, ValueInformationBlock};
+use super::variable_user_data::DataRecordError;
+use super::{DataRecords, FixedDataHeader, ManufacturerCode, Medium};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ManufacturerDataError {
+    /// No more decoders can be registered
+    RegistryFull,
+    DataRecordError(DataRecordError),
+}
+
+impl From<DataRecordError> for ManufacturerDataError {
+    fn from(error: DataRecordError) -> Self {
+        Self::DataRecordError(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for ManufacturerDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManufacturerDataError::RegistryFull => write!(f, "Decoder registry full"),
+            ManufacturerDataError::DataRecordError(error) => {
+                write!(f, "Invalid manufacturer specific data: {:?}", error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ManufacturerDataError {}
+
+/// A value decoded from the manufacturer specific data
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ManufacturerRecord<'a> {
+    /// Name of the value as given by the decoder, e.g. `"info codes"`
+    pub label: &'static str,
+    pub value: DataType<'a>,
+}
+
+/// Turns the manufacturer specific data of a meter into records
+pub trait ManufacturerDecoder {
+    /// Passes every record found in `data`, the bytes after the DIF 0x0F or 0x1F, to `emit`
+    fn decode<'a>(
+        &self,
+        data: &'a [u8],
+        emit: &mut dyn FnMut(ManufacturerRecord<'a>),
+    ) -> Result<(), DataRecordError>;
+}
+
+struct Registration<'d> {
+    manufacturer: ManufacturerCode,
+    version: Option<u8>,
+    medium: Option<Medium>,
+    decoder: &'d dyn ManufacturerDecoder,
+}
+
+impl Registration<'_> {
+    fn matches(&self, header: &FixedDataHeader) -> bool {
+        header.manufacturer.as_ref() == Ok(&self.manufacturer)
+            && self.version.is_none_or(|version| version == header.version)
+            && self
+                .medium
+                .as_ref()
+                .is_none_or(|medium| *medium == header.medium)
+    }
+}
+
+/// Decoders for up to `N` meter types, the first matching registration is used
+pub struct ManufacturerDecoders<'d, const N: usize> {
+    registrations: ArrayVec<Registration<'d>, N>,
+}
+
+impl<const N: usize> Default for ManufacturerDecoders<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'d, const N: usize> ManufacturerDecoders<'d, N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            registrations: ArrayVec::new_const(),
+        }
+    }
+
+    /// Uses `decoder` for all meters of the manufacturer
+    pub fn register(
+        &mut self,
+        manufacturer: ManufacturerCode,
+        decoder: &'d dyn ManufacturerDecoder,
+    ) -> Result<(), ManufacturerDataError> {
+        self.register_for(manufacturer, None, None, decoder)
+    }
+
+    /// Uses `decoder` for the meters of the manufacturer with the given version and medium
+    pub fn register_for(
+        &mut self,
+        manufacturer: ManufacturerCode,
+        version: Option<u8>,
+        medium: Option<Medium>,
+        decoder: &'d dyn ManufacturerDecoder,
+    ) -> Result<(), ManufacturerDataError> {
+        self.registrations
+            .try_push(Registration {
+                manufacturer,
+                version,
+                medium,
+                decoder,
+            })
+            .map_err(|_| ManufacturerDataError::RegistryFull)
+    }
+
+    /// The decoder registered for the meter described by `header`
+    #[must_use]
+    pub fn find(&self, header: &FixedDataHeader) -> Option<&'d dyn ManufacturerDecoder> {
+        self.registrations
+            .iter()
+            .find(|registration| registration.matches(header))
+            .map(|registration| registration.decoder)
+    }
+
+    /// Decodes the manufacturer specific data of `records`, returns false if there is no such
+    /// data or no decoder for the meter
+    pub fn decode<'a>(
+        &self,
+        header: &FixedDataHeader,
+        records: &DataRecords<'a>,
+        emit: &mut dyn FnMut(ManufacturerRecord<'a>),
+    ) -> Result<bool, ManufacturerDataError> {
+        let (Some(decoder), Some(data)) = (self.find(header), records.manufacturer_specific_data())
+        else {
+            return Ok(false);
+        };
+        decoder.decode(data, emit)?;
+        Ok(true)
+    }
+}
+
+/// Meaning of a manufacturer specific VIF, or VIF and VIFEs, of a meter
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ManufacturerVif {
+    pub manufacturer: ManufacturerCode,
+    /// Restricts the entry to this version of the meter
+    pub version: Option<u8>,
+    /// The VIF followed by all VIFEs of the record, e.g. `[0xFF, 0x07]`
+    pub value_information: &'static [u8],
+    pub units: &'static [Unit],
+    pub decimal_scale_exponent: isize,
+    pub label: &'static str,
+}
+
+impl ManufacturerVif {
+    fn matches(&self, header: &FixedDataHeader, block: &ValueInformationBlock) -> bool {
+        let extension = block
+            .value_information_extension
+            .as_ref()
+            .map_or(&[][..], |extension| extension.as_slice());
+        header.manufacturer.as_ref() == Ok(&self.manufacturer)
+            && self.version.is_none_or(|version| version == header.version)
+            && self
+                .value_information
+                .split_first()
+                .is_some_and(|(vif, vifes)| {
+                    *vif == block.value_information.data
+                        && vifes.len() == extension.len()
+                        && vifes
+                            .iter()
+                            .zip(extension)
+                            .all(|(vife, extension)| *vife == extension.data)
+                })
+    }
+
+    /// The value information to render the record with, the label is kept in `label`
+    #[must_use]
+    pub fn value_information(&self) -> ValueInformation {
+        ValueInformation {
+            decimal_offset_exponent: 0,
+            labels: ArrayVec::new(),
+            decimal_scale_exponent: self.decimal_scale_exponent,
+            units: self.units.iter().copied().take(10).collect(),
+        }
+    }
+}
+
+const VOLT: [Unit; 1] = [Unit {
+    name: UnitName::Volt,
+    exponent: 1,
+}];
+
+const AMPERE: [Unit; 1] = [Unit {
+    name: UnitName::Ampere,
+    exponent: 1,
+}];
+
+const WATT: [Unit; 1] = [Unit {
+    name: UnitName::Watt,
+    exponent: 1,
+}];
+
+/// Manufacturer specific codes found in the rscada test frames whose meaning is known from
+/// the reference decodings. The Elster and Engelmann frames of the corpus use no manufacturer
+/// specific VIF or VIFE codes.
+pub static BUILT_IN_VIFS: [ManufacturerVif; 10] = [
+    // the last record of the Sensus PolluStat, its meaning is not published
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'E', 'N'],
+        },
+        version: Some(0x06),
+        value_information: &[0x7F],
+        units: &[],
+        decimal_scale_exponent: 0,
+        label: "manufacturer specific",
+    },
+    // voltage, current and power of the Saia-Burgess ALE3 per phase, following the VIFE 0xFF
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xFD, 0xC9, 0xFF, 0x01],
+        units: &VOLT,
+        decimal_scale_exponent: 0,
+        label: "voltage L1",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xFD, 0xC9, 0xFF, 0x02],
+        units: &VOLT,
+        decimal_scale_exponent: 0,
+        label: "voltage L2",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xFD, 0xC9, 0xFF, 0x03],
+        units: &VOLT,
+        decimal_scale_exponent: 0,
+        label: "voltage L3",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xFD, 0xDB, 0xFF, 0x01],
+        units: &AMPERE,
+        decimal_scale_exponent: -1,
+        label: "current L1",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xFD, 0xDB, 0xFF, 0x02],
+        units: &AMPERE,
+        decimal_scale_exponent: -1,
+        label: "current L2",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xFD, 0xDB, 0xFF, 0x03],
+        units: &AMPERE,
+        decimal_scale_exponent: -1,
+        label: "current L3",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xAC, 0xFF, 0x01],
+        units: &WATT,
+        decimal_scale_exponent: 1,
+        label: "power L1",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xAC, 0xFF, 0x02],
+        units: &WATT,
+        decimal_scale_exponent: 1,
+        label: "power L2",
+    },
+    ManufacturerVif {
+        manufacturer: ManufacturerCode {
+            code: ['S', 'B', 'C'],
+        },
+        version: None,
+        value_information: &[0xAC, 0xFF, 0x03],
+        units: &WATT,
+        decimal_scale_exponent: 1,
+        label: "power L3",
+    },
+];
+
+/// Manufacturer specific VIF codes of up to `N` meters, the first matching entry is used
+pub struct ManufacturerVifs<'r, const N: usize> {
+    entries: ArrayVec<&'r ManufacturerVif, N>,
+}
+
+impl<const N: usize> Default for ManufacturerVifs<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'r, const N: usize> ManufacturerVifs<'r, N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: ArrayVec::new_const(),
+        }
+    }
+
+    pub fn register(&mut self, entry: &'r ManufacturerVif) -> Result<(), ManufacturerDataError> {
+        self.entries
+            .try_push(entry)
+            .map_err(|_| ManufacturerDataError::RegistryFull)
+    }
+
+    /// Registers the entries of `BUILT_IN_VIFS` after the ones registered so far
+    pub fn register_built_in(&mut self) -> Result<(), ManufacturerDataError> {
+        BUILT_IN_VIFS
+            .iter()
+            .try_for_each(|entry| self.register(entry))
+    }
+
+    /// The entry for the value information block of a record of the meter described by
+    /// `header`, only blocks with a manufacturer specific VIF or VIFE are looked up
+    #[must_use]
+    pub fn find(
+        &self,
+        header: &FixedDataHeader,
+        block: &ValueInformationBlock,
+    ) -> Option<&'r ManufacturerVif> {
+        if !block.is_manufacturer_specific() {
+            return None;
+        }
+        self.entries
+            .iter()
+            .copied()
+            .find(|entry| entry.matches(header, block))
+    }
+
+    /// The entry for `record`, records without value information block have none
+    #[must_use]
+    pub fn find_for_record(
+        &self,
+        header: &FixedDataHeader,
+        record: &DataRecord,
+    ) -> Option<&'r ManufacturerVif> {
+        record
+            .data_record_header
+            .raw_data_record_header
+            .value_information_block
+            .as_ref()
+            .and_then(|block| self.find(header, block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_data::data_information::DataType;
+    use crate::user_data::value_information::UnitName;
+    use crate::user_data::UserDataBlock;
+
+    /// Reads a status byte followed by a 16 bit counter
+    struct StatusDecoder;
+
+    impl ManufacturerDecoder for StatusDecoder {
+        fn decode<'a>(
+            &self,
+            data: &'a [u8],
+            emit: &mut dyn FnMut(ManufacturerRecord<'a>),
+        ) -> Result<(), DataRecordError> {
+            let status = *data.first().ok_or(DataRecordError::InsufficientData)?;
+            let counter = data
+                .get(1..3)
+                .and_then(|bytes| <[u8; 2]>::try_from(bytes).ok())
+                .ok_or(DataRecordError::InsufficientData)?;
+            emit(ManufacturerRecord {
+                label: "status",
+                value: DataType::Number(f64::from(status)),
+            });
+            emit(ManufacturerRecord {
+                label: "counter",
+                value: DataType::Number(f64::from(u16::from_le_bytes(counter))),
+            });
+            Ok(())
+        }
+    }
+
+    const USER_DATA: [u8; 22] = [
+        0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2A, 0x00, 0x00, 0x00, 0x01, 0x13,
+        0x05, 0x0F, 0x03, 0x34, 0x12, 0x2F, 0x2F,
+    ];
+
+    #[test]
+    fn test_manufacturer_specific_record() {
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(USER_DATA.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        let records = DataRecords::new(variable_data_block, Some(&fixed_data_header));
+        assert_eq!(
+            records.manufacturer_specific_data(),
+            Some([0x03, 0x34, 0x12, 0x2F, 0x2F].as_slice())
+        );
+        let last = records
+            .clone()
+            .flatten()
+            .last()
+            .map(|record| record.data.value);
+        assert_eq!(
+            last,
+            Some(Some(DataType::ManufacturerSpecific(&[
+                0x0F, 0x03, 0x34, 0x12, 0x2F, 0x2F
+            ])))
+        );
+
+        let more_records = [0x01, 0x13, 0x05, 0x1F, 0xAA];
+        let records = DataRecords::new(&more_records, None);
+        assert_eq!(records.clone().flatten().count(), 2);
+        assert!(records.more_records_follow());
+        assert_eq!(
+            records.manufacturer_specific_data(),
+            Some([0xAA].as_slice())
+        );
+        assert_eq!(
+            DataRecords::new(&more_records[..3], None).manufacturer_specific_data(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_registered_decoder() {
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(USER_DATA.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        let records = DataRecords::new(variable_data_block, Some(&fixed_data_header));
+        let decoder = StatusDecoder;
+        let mut decoders = ManufacturerDecoders::<1>::new();
+
+        let mut decoded = ArrayVec::<ManufacturerRecord, 4>::new();
+        assert_eq!(
+            decoders.decode(&fixed_data_header, &records, &mut |record| decoded
+                .push(record)),
+            Ok(false)
+        );
+
+        let manufacturer = ManufacturerCode::from_id(0x1EE6).unwrap();
+        assert_eq!(
+            decoders.register_for(manufacturer, Some(0x02), None, &decoder),
+            Ok(())
+        );
+        assert!(decoders.find(&fixed_data_header).is_none());
+        decoders = ManufacturerDecoders::new();
+        let manufacturer = ManufacturerCode::from_id(0x1EE6).unwrap();
+        assert_eq!(decoders.register(manufacturer, &decoder), Ok(()));
+        let manufacturer = ManufacturerCode::from_id(0x1EE6).unwrap();
+        assert_eq!(
+            decoders.register(manufacturer, &decoder),
+            Err(ManufacturerDataError::RegistryFull)
+        );
+
+        assert_eq!(
+            decoders.decode(&fixed_data_header, &records, &mut |record| decoded
+                .push(record)),
+            Ok(true)
+        );
+        assert_eq!(
+            decoded.as_slice(),
+            &[
+                ManufacturerRecord {
+                    label: "status",
+                    value: DataType::Number(3.0),
+                },
+                ManufacturerRecord {
+                    label: "counter",
+                    value: DataType::Number(4660.0),
+                },
+            ]
+        );
+    }
+
+    const CELSIUS: [Unit; 1] = [Unit {
+        name: UnitName::Celsius,
+        exponent: 1,
+    }];
+
+    #[test]
+    fn test_manufacturer_vif() {
+        let user_data = [
+            0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2A, 0x00, 0x00, 0x00, 0x02,
+            0xFF, 0x07, 0x34, 0x12, 0x01, 0x7F, 0x05,
+        ];
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(user_data.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        let temperature = ManufacturerVif {
+            manufacturer: ManufacturerCode::from_id(0x1EE6).unwrap(),
+            version: Some(0x01),
+            value_information: &[0xFF, 0x07],
+            units: &CELSIUS,
+            decimal_scale_exponent: -2,
+            label: "sensor temperature",
+        };
+        let status = ManufacturerVif {
+            manufacturer: ManufacturerCode::from_id(0x1EE6).unwrap(),
+            version: Some(0x02),
+            value_information: &[0x7F],
+            units: &[],
+            decimal_scale_exponent: 0,
+            label: "status",
+        };
+        let mut vifs = ManufacturerVifs::<2>::new();
+        assert_eq!(vifs.register(&temperature), Ok(()));
+        assert_eq!(vifs.register(&status), Ok(()));
+        assert_eq!(
+            vifs.register(&status),
+            Err(ManufacturerDataError::RegistryFull)
+        );
+
+        let mut records = DataRecords::new(variable_data_block, Some(&fixed_data_header));
+        let record = records.next().unwrap().unwrap();
+        let entry = vifs.find_for_record(&fixed_data_header, &record).unwrap();
+        assert_eq!(entry.label, "sensor temperature");
+        let value_information = entry.value_information();
+        assert_eq!(value_information.units.as_slice(), CELSIUS.as_slice());
+        assert_eq!(value_information.decimal_scale_exponent, -2);
+        assert!(value_information.labels.is_empty());
+
+        // the status code is only registered for version 2
+        let record = records.next().unwrap().unwrap();
+        assert!(vifs.find_for_record(&fixed_data_header, &record).is_none());
+    }
+
+    fn built_in_labels(user_data: &[u8]) -> ArrayVec<&'static str, 16> {
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(user_data)
+        else {
+            panic!("expected a variable data structure");
+        };
+        let mut vifs = ManufacturerVifs::<{ BUILT_IN_VIFS.len() }>::new();
+        assert_eq!(vifs.register_built_in(), Ok(()));
+
+        let mut labels = ArrayVec::new();
+        for record in DataRecords::new(variable_data_block, Some(&fixed_data_header)) {
+            let record = record.unwrap();
+            if let Some(entry) = vifs.find_for_record(&fixed_data_header, &record) {
+                labels.push(entry.label);
+            }
+        }
+        labels
+    }
+
+    #[test]
+    fn test_built_in_vifs() {
+        // user data of tests/rscada/test-frames/SEN_Pollustat.hex
+        let user_data = [
+            0x72, 0x88, 0x17, 0x01, 0x00, 0xAE, 0x4C, 0x06, 0x0D, 0x3E, 0x00, 0x00, 0x00, 0x04,
+            0x6D, 0x3B, 0x0E, 0xE7, 0x14, 0x34, 0x6D, 0x00, 0x00, 0x01, 0x01, 0x34, 0xFD, 0x17,
+            0x00, 0x00, 0x00, 0x04, 0x04, 0x20, 0x92, 0x22, 0xF1, 0x00, 0x04, 0x24, 0xA4, 0x1A,
+            0xE7, 0x00, 0x04, 0x86, 0x3B, 0x97, 0x9B, 0x00, 0x00, 0x04, 0x13, 0xBE, 0x09, 0x5E,
+            0x00, 0x05, 0x2E, 0xB1, 0xD1, 0x2E, 0xBE, 0x05, 0x3E, 0xF5, 0xB8, 0x4E, 0x40, 0x05,
+            0x5B, 0xB8, 0x2D, 0xF9, 0x41, 0x05, 0x5F, 0x78, 0x8B, 0xF9, 0x41, 0x05, 0x63, 0x00,
+            0x80, 0x3B, 0xBD, 0x04, 0xBE, 0x50, 0x71, 0xBB, 0xB0, 0x00, 0x04, 0xBE, 0x58, 0xF4,
+            0x02, 0x00, 0x00, 0x0C, 0x78, 0x88, 0x17, 0x01, 0x00, 0x02, 0x7F, 0x10, 0xB5,
+        ];
+        assert_eq!(
+            built_in_labels(&user_data).as_slice(),
+            &["manufacturer specific"]
+        );
+
+        // user data of tests/rscada/test-frames/SBC_Saia-Burgess-ALE3.hex
+        let user_data = [
+            0x72, 0x55, 0x00, 0x00, 0x19, 0x43, 0x4C, 0x16, 0x02, 0xBF, 0x00, 0x00, 0x00, 0x8C,
+            0x10, 0x04, 0x93, 0x02, 0x00, 0x00, 0x8C, 0x11, 0x04, 0x93, 0x02, 0x00, 0x00, 0x8C,
+            0x20, 0x04, 0x06, 0x00, 0x00, 0x00, 0x8C, 0x21, 0x04, 0x06, 0x00, 0x00, 0x00, 0x02,
+            0xFD, 0xC9, 0xFF, 0x01, 0xDF, 0x00, 0x02, 0xFD, 0xDB, 0xFF, 0x01, 0x00, 0x00, 0x02,
+            0xAC, 0xFF, 0x01, 0x00, 0x00, 0x82, 0x40, 0xAC, 0xFF, 0x01, 0x00, 0x00, 0x02, 0xFD,
+            0xC9, 0xFF, 0x02, 0x00, 0x00, 0x02, 0xFD, 0xDB, 0xFF, 0x02, 0x00, 0x00, 0x02, 0xAC,
+            0xFF, 0x02, 0x00, 0x00, 0x82, 0x40, 0xAC, 0xFF, 0x02, 0x00, 0x00, 0x02, 0xFD, 0xC9,
+            0xFF, 0x03, 0x00, 0x00, 0x02, 0xFD, 0xDB, 0xFF, 0x03, 0x00, 0x00, 0x02, 0xAC, 0xFF,
+            0x03, 0x00, 0x00, 0x82, 0x40, 0xAC, 0xFF, 0x03, 0x00, 0x00, 0x02, 0xFF, 0x68, 0x00,
+            0x00, 0x02, 0xAC, 0xFF, 0x00, 0x00, 0x00, 0x82, 0x40, 0xAC, 0xFF, 0x00, 0x00, 0x00,
+            0x01, 0xFF, 0x14, 0x00,
+        ];
+        assert_eq!(
+            built_in_labels(&user_data).as_slice(),
+            &[
+                "voltage L1",
+                "current L1",
+                "power L1",
+                "power L1",
+                "voltage L2",
+                "current L2",
+                "power L2",
+                "power L2",
+                "voltage L3",
+                "current L3",
+                "power L3",
+                "power L3",
+            ]
+        );
+
+        // user data of tests/rscada/test-frames/els_falcon.hex, the VIFE 0x7E of the date is a
+        // standard code and not looked up
+        let user_data = [
+            0x72, 0x45, 0x23, 0x11, 0x70, 0x93, 0x15, 0x0A, 0x07, 0x02, 0x00, 0x00, 0x00, 0x0C,
+            0x13, 0x67, 0x45, 0x23, 0x01, 0x04, 0x6D, 0x3A, 0x0D, 0xE6, 0x02, 0x42, 0x6C, 0xE1,
+            0x01, 0x4C, 0x13, 0x51, 0x69, 0x45, 0x00, 0x42, 0xEC, 0x7E, 0x01, 0x11, 0x12, 0x3B,
+            0x39, 0x17, 0x42, 0x6C, 0x01, 0x11, 0x02, 0x3B, 0xF9, 0x17, 0x0F, 0x0E, 0x42, 0x20,
+            0x01, 0x01, 0x01, 0x00, 0x05, 0x08, 0x5E, 0x01, 0x20, 0x3D, 0x12, 0x08, 0x3D, 0x12,
+            0x08, 0x00,
+        ];
+        assert!(built_in_labels(&user_data).is_empty());
+    }
+}
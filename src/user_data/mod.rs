@@ -5,12 +5,20 @@ use std::fmt;
 use variable_user_data::DataRecordError;
 
 use self::data_record::DataRecord;
+use crate::afl::{AflError, AuthenticationFragmentationLayer};
 
+pub mod compact_frame;
+pub mod configuration_field;
 pub mod data_information;
 pub mod data_record;
+pub mod manufacturer_specific;
+pub mod secondary_address;
 pub mod value_information;
 pub mod variable_user_data;
 
+pub use configuration_field::ConfigurationField;
+pub use secondary_address::SecondaryAddress;
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(into = "Vec<DataRecord>"))]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -19,6 +27,10 @@ pub struct DataRecords<'a> {
     offset: usize,
     data: &'a [u8],
     fixed_data_header: Option<&'a FixedDataHeader>,
+    /// Offset of the DIF 0x1F once the iteration reached it
+    more_records_offset: Option<usize>,
+    /// Offset of the DIF 0x0F or 0x1F once the iteration reached it
+    manufacturer_specific_offset: Option<usize>,
 }
 
 #[cfg(feature = "serde")]
@@ -33,14 +45,17 @@ impl<'a> Iterator for DataRecords<'a> {
     type Item = Result<DataRecord<'a>, DataRecordError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut _more_records_follow = false;
-
         while self.offset < self.data.len() {
             match self.data.get(self.offset)? {
-                0x1F => {
-                    /* TODO: parse manufacturer specific */
-                    _more_records_follow = true;
+                dif @ (0x0F | 0x1F) => {
+                    // the manufacturer specific data takes up the rest of the records
+                    if *dif == 0x1F {
+                        self.more_records_offset = Some(self.offset);
+                    }
+                    self.manufacturer_specific_offset = Some(self.offset);
+                    let record = DataRecord::try_from(self.data.get(self.offset..)?);
                     self.offset = self.data.len();
+                    return Some(record);
                 }
                 0x2F => {
                     self.offset += 1;
@@ -71,8 +86,41 @@ impl<'a> DataRecords<'a> {
             offset: 0,
             data,
             fixed_data_header,
+            more_records_offset: None,
+            manufacturer_specific_offset: None,
         }
     }
+
+    fn find_more_records_offset(&self) -> Option<usize> {
+        let mut records = Self::new(self.data, self.fixed_data_header);
+        records.by_ref().for_each(drop);
+        records.more_records_offset
+    }
+
+    /// Returns true if the records end with DIF 0x1F, i.e. the slave has more records
+    /// to send in the next telegram
+    #[must_use]
+    pub fn more_records_follow(&self) -> bool {
+        self.find_more_records_offset().is_some()
+    }
+
+    /// The data following the DIF 0x0F or 0x1F, if any
+    #[must_use]
+    pub fn manufacturer_specific_data(&self) -> Option<&'a [u8]> {
+        let mut records = Self::new(self.data, self.fixed_data_header);
+        records.by_ref().for_each(drop);
+        records
+            .data
+            .get(records.manufacturer_specific_offset? + 1..)
+    }
+
+    /// The bytes holding the records, without the DIF 0x1F and the data following it
+    #[must_use]
+    pub fn record_data(&self) -> &'a [u8] {
+        self.find_more_records_offset()
+            .and_then(|offset| self.data.get(..offset))
+            .unwrap_or(self.data)
+    }
 }
 
 bitflags::bitflags! {
@@ -145,6 +193,9 @@ impl From<ControlInformation> for Direction {
     fn from(single_byte: ControlInformation) -> Self {
         match single_byte {
             ControlInformation::ResetAtApplicationLevel => Self::MasterToSlave,
+            ControlInformation::ResetAtApplicationLevelWithLongHeader => Self::MasterToSlave,
+            ControlInformation::RequestSelectedApplicationWithLongHeader => Self::MasterToSlave,
+            ControlInformation::TimeSynchronization => Self::MasterToSlave,
             ControlInformation::SendData => Self::MasterToSlave,
             ControlInformation::SelectSlave => Self::MasterToSlave,
             ControlInformation::SynchronizeSlave => Self::MasterToSlave,
@@ -162,12 +213,29 @@ impl From<ControlInformation> for Direction {
             ControlInformation::ReadEEPROM => Self::MasterToSlave,
             ControlInformation::StartSoftwareTest => Self::MasterToSlave,
             ControlInformation::HashProcedure(_) => Self::MasterToSlave,
+            // used in both directions, mostly by meters
+            ControlInformation::AuthenticationAndFragmentationLayer => Self::SlaveToMaster,
+            ControlInformation::ExtendedLinkLayer => Self::SlaveToMaster,
+            ControlInformation::SendDataWithShortHeader => Self::MasterToSlave,
+            ControlInformation::SendDataWithLongHeader => Self::MasterToSlave,
+            ControlInformation::TransportLayerToMeter => Self::MasterToSlave,
             ControlInformation::SendErrorStatus => Self::SlaveToMaster,
             ControlInformation::SendAlarmStatus => Self::SlaveToMaster,
+            ControlInformation::SendErrorStatusWithShortHeader => Self::SlaveToMaster,
+            ControlInformation::SendErrorStatusWithLongHeader => Self::SlaveToMaster,
+            ControlInformation::SendAlarmStatusWithShortHeader => Self::SlaveToMaster,
+            ControlInformation::SendAlarmStatusWithLongHeader => Self::SlaveToMaster,
+            ControlInformation::ResponseSelectedApplication => Self::SlaveToMaster,
             ControlInformation::ResponseWithVariableDataStructure { lsb_order: _ } => {
                 Self::SlaveToMaster
             }
             ControlInformation::ResponseWithFixedDataStructure => Self::SlaveToMaster,
+            ControlInformation::ResponseWithShortHeader => Self::SlaveToMaster,
+            ControlInformation::ResponseWithoutHeader => Self::SlaveToMaster,
+            ControlInformation::FormatFrame => Self::SlaveToMaster,
+            ControlInformation::CompactFrame => Self::SlaveToMaster,
+            ControlInformation::TransportLayerShortHeader => Self::SlaveToMaster,
+            ControlInformation::TransportLayerLongHeader => Self::SlaveToMaster,
         }
     }
 }
@@ -193,10 +261,49 @@ pub enum ControlInformation {
     ReadEEPROM,
     StartSoftwareTest,
     HashProcedure(u8),
+    AuthenticationAndFragmentationLayer,
+    /// Extended link layer of wireless telegrams (CI 0x8C to 0x8F)
+    ExtendedLinkLayer,
     SendErrorStatus,
     SendAlarmStatus,
-    ResponseWithVariableDataStructure { lsb_order: bool },
+    /// Application error with short header (CI 0x6E)
+    SendErrorStatusWithShortHeader,
+    /// Application error with long header (CI 0x6F)
+    SendErrorStatusWithLongHeader,
+    /// Alarm with short header (CI 0x74)
+    SendAlarmStatusWithShortHeader,
+    /// Alarm with long header (CI 0x75)
+    SendAlarmStatusWithLongHeader,
+    /// Application reset or select with long header (CI 0x53)
+    ResetAtApplicationLevelWithLongHeader,
+    /// Request of the selected application with long header (CI 0x55)
+    RequestSelectedApplicationWithLongHeader,
+    /// Response of the selected application with short header (CI 0x7B)
+    ResponseSelectedApplication,
+    /// Time synchronisation with long header (CI 0x6C and 0x6D)
+    TimeSynchronization,
+    ResponseWithVariableDataStructure {
+        lsb_order: bool,
+    },
     ResponseWithFixedDataStructure,
+    /// Application data to the meter with short header (CI 0x5A)
+    SendDataWithShortHeader,
+    /// Application data to the meter with long header (CI 0x5B)
+    SendDataWithLongHeader,
+    /// Response with variable data structure and short header (CI 0x7A)
+    ResponseWithShortHeader,
+    /// Response with variable data structure without header (CI 0x78)
+    ResponseWithoutHeader,
+    /// Headers of the records without their data and without header (CI 0x69)
+    FormatFrame,
+    /// Data of the records without their headers and without header (CI 0x79)
+    CompactFrame,
+    /// Transport layer with long header to the meter, no application data (CI 0x80)
+    TransportLayerToMeter,
+    /// Transport layer with short header from the meter, no application data (CI 0x8A)
+    TransportLayerShortHeader,
+    /// Transport layer with long header from the meter, no application data (CI 0x8B)
+    TransportLayerLongHeader,
 }
 
 impl ControlInformation {
@@ -219,13 +326,32 @@ impl ControlInformation {
             0xB3 => Ok(Self::StartCalibrationTestMode),
             0xB4 => Ok(Self::ReadEEPROM),
             0xB6 => Ok(Self::StartSoftwareTest),
-            0x90..=0x97 => Ok(Self::HashProcedure(byte - 0x90)),
+            0x53 => Ok(Self::ResetAtApplicationLevelWithLongHeader),
+            0x55 => Ok(Self::RequestSelectedApplicationWithLongHeader),
+            0x6C | 0x6D => Ok(Self::TimeSynchronization),
+            0x90 => Ok(Self::AuthenticationAndFragmentationLayer),
+            0x8C..=0x8F => Ok(Self::ExtendedLinkLayer),
+            0x91..=0x97 => Ok(Self::HashProcedure(byte - 0x90)),
             0x70 => Ok(Self::SendErrorStatus),
             0x71 => Ok(Self::SendAlarmStatus),
+            0x6E => Ok(Self::SendErrorStatusWithShortHeader),
+            0x6F => Ok(Self::SendErrorStatusWithLongHeader),
+            0x74 => Ok(Self::SendAlarmStatusWithShortHeader),
+            0x75 => Ok(Self::SendAlarmStatusWithLongHeader),
             0x72 | 0x76 => Ok(Self::ResponseWithVariableDataStructure {
                 lsb_order: byte & 0x04 != 0,
             }),
             0x73 | 0x77 => Ok(Self::ResponseWithFixedDataStructure),
+            0x5A => Ok(Self::SendDataWithShortHeader),
+            0x5B => Ok(Self::SendDataWithLongHeader),
+            0x7A => Ok(Self::ResponseWithShortHeader),
+            0x7B => Ok(Self::ResponseSelectedApplication),
+            0x78 => Ok(Self::ResponseWithoutHeader),
+            0x69 => Ok(Self::FormatFrame),
+            0x79 => Ok(Self::CompactFrame),
+            0x80 => Ok(Self::TransportLayerToMeter),
+            0x8A => Ok(Self::TransportLayerShortHeader),
+            0x8B => Ok(Self::TransportLayerLongHeader),
             _ => Err(ApplicationLayerError::InvalidControlInformation { byte }),
         }
     }
@@ -236,10 +362,30 @@ impl ControlInformation {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ApplicationLayerError {
     MissingControlInformation,
-    InvalidControlInformation { byte: u8 },
-    IdentificationNumberError { digits: [u8; 4], number: u32 },
-    InvalidManufacturerCode { code: u16 },
+    InvalidControlInformation {
+        byte: u8,
+    },
+    IdentificationNumberError {
+        digits: [u8; 4],
+        number: u32,
+    },
+    InvalidManufacturerCode {
+        code: u16,
+    },
     InsufficientData,
+    UnsupportedControlInformation {
+        byte: u8,
+    },
+    InvalidSecondaryAddress,
+    AflError(AflError),
+    /// Fragmented messages have to be reassembled first
+    FragmentedMessage,
+}
+
+impl From<AflError> for ApplicationLayerError {
+    fn from(error: AflError) -> Self {
+        Self::AflError(error)
+    }
 }
 
 #[cfg(feature = "std")]
@@ -265,6 +411,14 @@ impl fmt::Display for ApplicationLayerError {
             ApplicationLayerError::InsufficientData => {
                 write!(f, "Insufficient data")
             }
+            ApplicationLayerError::UnsupportedControlInformation { byte } => {
+                write!(f, "Unsupported control information: {}", byte)
+            }
+            ApplicationLayerError::InvalidSecondaryAddress => {
+                write!(f, "Invalid secondary address")
+            }
+            ApplicationLayerError::AflError(error) => write!(f, "{}", error),
+            ApplicationLayerError::FragmentedMessage => write!(f, "Fragmented message"),
         }
     }
 }
@@ -272,6 +426,60 @@ impl fmt::Display for ApplicationLayerError {
 #[cfg(feature = "std")]
 impl std::error::Error for ApplicationLayerError {}
 
+/// Error reported by a slave with CI 0x70
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ApplicationError {
+    /// Also used if the slave sends no error code
+    Unspecified,
+    UnimplementedControlInformation,
+    /// The response was truncated
+    BufferTooLong,
+    TooManyRecords,
+    PrematureEndOfRecord,
+    TooManyDifes,
+    TooManyVifes,
+    ApplicationBusy,
+    TooManyReadouts,
+    Reserved(u8),
+}
+
+impl From<u8> for ApplicationError {
+    fn from(code: u8) -> Self {
+        match code {
+            0x00 => Self::Unspecified,
+            0x01 => Self::UnimplementedControlInformation,
+            0x02 => Self::BufferTooLong,
+            0x03 => Self::TooManyRecords,
+            0x04 => Self::PrematureEndOfRecord,
+            0x05 => Self::TooManyDifes,
+            0x06 => Self::TooManyVifes,
+            0x08 => Self::ApplicationBusy,
+            0x09 => Self::TooManyReadouts,
+            code => Self::Reserved(code),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ApplicationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unspecified => write!(f, "Unspecified error"),
+            Self::UnimplementedControlInformation => write!(f, "Unimplemented CI-Field"),
+            Self::BufferTooLong => write!(f, "Buffer too long, truncated"),
+            Self::TooManyRecords => write!(f, "Too many records"),
+            Self::PrematureEndOfRecord => write!(f, "Premature end of record"),
+            Self::TooManyDifes => write!(f, "More than 10 DIFEs"),
+            Self::TooManyVifes => write!(f, "More than 10 VIFEs"),
+            Self::ApplicationBusy => write!(f, "Application busy"),
+            Self::TooManyReadouts => write!(f, "Too many readouts"),
+            Self::Reserved(code) => write!(f, "Reserved error code: {:#04X}", code),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -344,6 +552,29 @@ impl ApplicationResetSubcode {
     }
 }
 
+impl From<&ApplicationResetSubcode> for u8 {
+    fn from(subcode: &ApplicationResetSubcode) -> Self {
+        match *subcode {
+            ApplicationResetSubcode::All(value)
+            | ApplicationResetSubcode::UserData(value)
+            | ApplicationResetSubcode::SimpleBilling(value)
+            | ApplicationResetSubcode::EnhancedBilling(value)
+            | ApplicationResetSubcode::MultiTariffBilling(value)
+            | ApplicationResetSubcode::InstantaneousValues(value)
+            | ApplicationResetSubcode::LoadManagementValues(value)
+            | ApplicationResetSubcode::Reserved1(value)
+            | ApplicationResetSubcode::InstallationStartup(value)
+            | ApplicationResetSubcode::Testing(value)
+            | ApplicationResetSubcode::Calibration(value)
+            | ApplicationResetSubcode::ConfigurationUpdates(value)
+            | ApplicationResetSubcode::Manufacturing(value)
+            | ApplicationResetSubcode::Development(value)
+            | ApplicationResetSubcode::Selftest(value)
+            | ApplicationResetSubcode::Reserved2(value) => value,
+        }
+    }
+}
+
 fn bcd_hex_digits_to_u32(digits: [u8; 4]) -> Result<u32, ApplicationLayerError> {
     let mut number = 0u32;
 
@@ -424,7 +655,16 @@ pub struct FixedDataHeder {
 pub enum UserDataBlock<'a> {
     ResetAtApplicationLevel {
         subcode: ApplicationResetSubcode,
+        /// Long header of CI 0x53
+        header: Option<TransportHeader>,
+    },
+    /// Data records sent from the master to the slave with SND_UD, e.g. to set the primary address
+    SendData {
+        #[cfg_attr(feature = "serde", serde(skip_serializing))]
+        variable_data_block: &'a [u8],
     },
+    /// Selection of a slave by its secondary address, which may contain wildcards
+    SelectSlave { secondary_address: SecondaryAddress },
     FixedDataStructure {
         identification_number: IdentificationNumber,
         access_number: u8,
@@ -438,6 +678,103 @@ pub enum UserDataBlock<'a> {
         #[cfg_attr(feature = "serde", serde(skip_serializing))]
         variable_data_block: &'a [u8],
     },
+    /// Response with a short header, e.g. of wireless meters whose address is part of the
+    /// link layer
+    VariableDataStructureWithShortHeader {
+        short_header: ShortHeader,
+        #[cfg_attr(feature = "serde", serde(skip_serializing))]
+        variable_data_block: &'a [u8],
+    },
+    VariableDataStructureWithoutHeader {
+        #[cfg_attr(feature = "serde", serde(skip_serializing))]
+        variable_data_block: &'a [u8],
+    },
+    /// Application data to the meter with a short or long header
+    SendDataWithHeader {
+        header: TransportHeader,
+        #[cfg_attr(feature = "serde", serde(skip_serializing))]
+        variable_data_block: &'a [u8],
+    },
+    /// Transport layer without application data, e.g. an acknowledge of a meter
+    TransportLayer { header: TransportHeader },
+    /// Switches the baud rate of the slave (CI 0xB8 to 0xBF)
+    SetBaudRate { baud_rate: u32 },
+    /// Error reported by the slave (CI 0x70, 0x6E and 0x6F)
+    ApplicationError {
+        error: ApplicationError,
+        /// Short or long header of CI 0x6E and 0x6F
+        header: Option<TransportHeader>,
+    },
+    /// Alarm reported by the slave (CI 0x71, 0x74 and 0x75), the meaning of the bits is
+    /// manufacturer specific
+    Alarm {
+        alarm_status: u8,
+        /// Short or long header of CI 0x74 and 0x75
+        header: Option<TransportHeader>,
+    },
+    /// Headers of the records, see `compact_frame::Format`
+    FormatFrame {
+        format_signature: u16,
+        #[cfg_attr(feature = "serde", serde(skip_serializing))]
+        headers: &'a [u8],
+    },
+    /// Data of the records, see `compact_frame::FormatCache`
+    CompactFrame {
+        format_signature: u16,
+        /// CRC over the records of the full frame
+        data_crc: u16,
+        #[cfg_attr(feature = "serde", serde(skip_serializing))]
+        data: &'a [u8],
+    },
+}
+
+impl<'a> UserDataBlock<'a> {
+    /// Access number, status and configuration field of the transport layer header, if any
+    #[must_use]
+    pub fn short_header(&self) -> Option<ShortHeader> {
+        match self {
+            UserDataBlock::VariableDataStructure {
+                fixed_data_header, ..
+            } => Some(ShortHeader::from(fixed_data_header)),
+            UserDataBlock::VariableDataStructureWithShortHeader { short_header, .. } => {
+                Some(*short_header)
+            }
+            UserDataBlock::SendDataWithHeader { header, .. }
+            | UserDataBlock::TransportLayer { header } => Some(header.short_header()),
+            UserDataBlock::ResetAtApplicationLevel { header, .. }
+            | UserDataBlock::ApplicationError { header, .. }
+            | UserDataBlock::Alarm { header, .. } => {
+                header.as_ref().map(TransportHeader::short_header)
+            }
+            _ => None,
+        }
+    }
+
+    /// The data records following the header, if any
+    #[must_use]
+    pub const fn variable_data_block(&self) -> Option<&'a [u8]> {
+        match self {
+            UserDataBlock::VariableDataStructure {
+                variable_data_block,
+                ..
+            }
+            | UserDataBlock::VariableDataStructureWithShortHeader {
+                variable_data_block,
+                ..
+            }
+            | UserDataBlock::VariableDataStructureWithoutHeader {
+                variable_data_block,
+            }
+            | UserDataBlock::SendData {
+                variable_data_block,
+            }
+            | UserDataBlock::SendDataWithHeader {
+                variable_data_block,
+                ..
+            } => Some(variable_data_block),
+            _ => None,
+        }
+    }
 }
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq)]
@@ -533,19 +870,138 @@ impl fmt::Display for Medium {
         write!(f, "{}", medium)
     }
 }
+/// Long header of the transport layer
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FixedDataHeader {
     pub identification_number: IdentificationNumber,
     pub manufacturer: Result<ManufacturerCode, ApplicationLayerError>,
+    /// The manufacturer as sent, also when it is no valid code
+    pub manufacturer_id: u16,
     pub version: u8,
     pub medium: Medium,
+    /// The medium as sent, `medium` maps several of them to the same variant
+    pub medium_byte: u8,
     pub access_number: u8,
     pub status: StatusField,
-    pub signature: u16,
+    pub configuration_field: ConfigurationField,
     pub lsb_order: bool,
 }
+
+impl FixedDataHeader {
+    /// Length of the header without the CI field
+    pub const LENGTH: usize = 12;
+
+    fn parse(data: &[u8], lsb_order: bool) -> Result<Self, ApplicationLayerError> {
+        let mut iter = data.iter();
+        let mut identification_number_bytes = [
+            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+        ];
+        if lsb_order {
+            identification_number_bytes.reverse();
+        }
+        let identification_number =
+            IdentificationNumber::from_bcd_hex_digits(identification_number_bytes)?;
+        let manufacturer_id = u16::from_le_bytes([
+            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+        ]);
+        let version = *iter.next().ok_or(ApplicationLayerError::InsufficientData)?;
+        let medium_byte = *iter.next().ok_or(ApplicationLayerError::InsufficientData)?;
+        let short_header = ShortHeader::parse(iter.as_slice())?;
+
+        Ok(Self {
+            identification_number,
+            manufacturer: ManufacturerCode::from_id(manufacturer_id),
+            manufacturer_id,
+            version,
+            medium: MeasuredMedium::new(medium_byte).medium,
+            medium_byte,
+            access_number: short_header.access_number,
+            status: short_header.status,
+            configuration_field: short_header.configuration_field,
+            lsb_order,
+        })
+    }
+}
+
+/// Short header of the transport layer, the address of the meter is taken from the link layer
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ShortHeader {
+    pub access_number: u8,
+    pub status: StatusField,
+    pub configuration_field: ConfigurationField,
+}
+
+impl ShortHeader {
+    /// Length of the header without the CI field
+    pub const LENGTH: usize = 4;
+
+    fn parse(data: &[u8]) -> Result<Self, ApplicationLayerError> {
+        let mut iter = data.iter();
+        Ok(Self {
+            access_number: *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+            status: StatusField::from_bits_truncate(
+                *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+            ),
+            configuration_field: ConfigurationField::from(u16::from_le_bytes([
+                *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+                *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+            ])),
+        })
+    }
+}
+
+impl From<&FixedDataHeader> for ShortHeader {
+    fn from(header: &FixedDataHeader) -> Self {
+        Self {
+            access_number: header.access_number,
+            status: header.status,
+            configuration_field: header.configuration_field,
+        }
+    }
+}
+
+/// Header of the transport layer used by CI fields that allow both kinds
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransportHeader {
+    Short(ShortHeader),
+    Long(FixedDataHeader),
+}
+
+impl TransportHeader {
+    #[must_use]
+    pub fn short_header(&self) -> ShortHeader {
+        match self {
+            TransportHeader::Short(header) => *header,
+            TransportHeader::Long(header) => ShortHeader::from(header),
+        }
+    }
+
+    fn parse(data: &[u8], long: bool) -> Result<(Self, &[u8]), ApplicationLayerError> {
+        let (header, length) = if long {
+            (
+                Self::Long(FixedDataHeader::parse(data, false)?),
+                FixedDataHeader::LENGTH,
+            )
+        } else {
+            (Self::Short(ShortHeader::parse(data)?), ShortHeader::LENGTH)
+        };
+        Ok((
+            header,
+            data.get(length..)
+                .ok_or(ApplicationLayerError::InsufficientData)?,
+        ))
+    }
+}
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -570,6 +1026,13 @@ impl ManufacturerCode {
             Err(ApplicationLayerError::InvalidManufacturerCode { code: id })
         }
     }
+
+    #[must_use]
+    pub const fn to_id(&self) -> u16 {
+        ((self.code[0] as u16).wrapping_sub(64) << 10)
+            | ((self.code[1] as u16).wrapping_sub(64) << 5)
+            | (self.code[2] as u16).wrapping_sub(64)
+    }
 }
 
 #[cfg(feature = "std")]
@@ -610,73 +1073,222 @@ impl<'a> TryFrom<&'a [u8]> for UserDataBlock<'a> {
 
         match control_information {
             ControlInformation::ResetAtApplicationLevel => {
-                let subcode = ApplicationResetSubcode::from(
-                    *data.get(1).ok_or(ApplicationLayerError::InsufficientData)?,
-                );
-                Ok(UserDataBlock::ResetAtApplicationLevel { subcode })
+                // without a subcode the whole application is reset
+                let subcode = ApplicationResetSubcode::from(data.get(1).copied().unwrap_or(0));
+                Ok(UserDataBlock::ResetAtApplicationLevel {
+                    subcode,
+                    header: None,
+                })
+            }
+            ControlInformation::ResetAtApplicationLevelWithLongHeader => {
+                let (header, data) = TransportHeader::parse(
+                    data.get(1..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                    true,
+                )?;
+                Ok(UserDataBlock::ResetAtApplicationLevel {
+                    subcode: ApplicationResetSubcode::from(data.first().copied().unwrap_or(0)),
+                    header: Some(header),
+                })
+            }
+            ControlInformation::SendData => Ok(UserDataBlock::SendData {
+                variable_data_block: data
+                    .get(1..)
+                    .ok_or(ApplicationLayerError::InsufficientData)?,
+            }),
+            ControlInformation::SelectSlave => {
+                let bytes = data
+                    .get(1..9)
+                    .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+                    .ok_or(ApplicationLayerError::InsufficientData)?;
+                Ok(UserDataBlock::SelectSlave {
+                    secondary_address: SecondaryAddress::from_bytes(bytes),
+                })
+            }
+            ControlInformation::SetBaudRate300 => Ok(UserDataBlock::SetBaudRate { baud_rate: 300 }),
+            ControlInformation::SetBaudRate600 => Ok(UserDataBlock::SetBaudRate { baud_rate: 600 }),
+            ControlInformation::SetBaudRate1200 => {
+                Ok(UserDataBlock::SetBaudRate { baud_rate: 1200 })
+            }
+            ControlInformation::SetBaudRate2400 => {
+                Ok(UserDataBlock::SetBaudRate { baud_rate: 2400 })
+            }
+            ControlInformation::SetBaudRate4800 => {
+                Ok(UserDataBlock::SetBaudRate { baud_rate: 4800 })
+            }
+            ControlInformation::SetBaudRate9600 => {
+                Ok(UserDataBlock::SetBaudRate { baud_rate: 9600 })
+            }
+            ControlInformation::SetBaudRate19200 => {
+                Ok(UserDataBlock::SetBaudRate { baud_rate: 19200 })
+            }
+            ControlInformation::SetBaudRate38400 => {
+                Ok(UserDataBlock::SetBaudRate { baud_rate: 38400 })
+            }
+            ControlInformation::SynchronizeSlave
+            | ControlInformation::OutputRAMContent
+            | ControlInformation::WriteRAMContent
+            | ControlInformation::StartCalibrationTestMode
+            | ControlInformation::ReadEEPROM
+            | ControlInformation::StartSoftwareTest
+            | ControlInformation::HashProcedure(_) => {
+                Err(ApplicationLayerError::UnsupportedControlInformation {
+                    byte: *data
+                        .first()
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                })
+            }
+            ControlInformation::AuthenticationAndFragmentationLayer => {
+                match AuthenticationFragmentationLayer::parse(data)? {
+                    (Some(afl), message) if !afl.more_fragments && afl.fragment_id <= 1 => {
+                        UserDataBlock::try_from(message)
+                    }
+                    _ => Err(ApplicationLayerError::FragmentedMessage),
+                }
+            }
+            ControlInformation::ExtendedLinkLayer => {
+                match crate::wmbus::ExtendedLinkLayer::parse(data)
+                    .map_err(|_| ApplicationLayerError::InsufficientData)?
+                {
+                    // an encrypted payload has to be decrypted with the session key first
+                    (Some(ell), message)
+                        if ell
+                            .session_number
+                            .is_none_or(|session_number| session_number.encryption == 0) =>
+                    {
+                        UserDataBlock::try_from(message)
+                    }
+                    _ => Err(ApplicationLayerError::UnsupportedControlInformation {
+                        byte: *data
+                            .first()
+                            .ok_or(ApplicationLayerError::InsufficientData)?,
+                    }),
+                }
+            }
+            ControlInformation::SendErrorStatus => Ok(UserDataBlock::ApplicationError {
+                error: data.get(1).map_or(ApplicationError::Unspecified, |code| {
+                    ApplicationError::from(*code)
+                }),
+                header: None,
+            }),
+            ControlInformation::SendErrorStatusWithShortHeader
+            | ControlInformation::SendErrorStatusWithLongHeader => {
+                let (header, data) = TransportHeader::parse(
+                    data.get(1..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                    control_information == ControlInformation::SendErrorStatusWithLongHeader,
+                )?;
+                Ok(UserDataBlock::ApplicationError {
+                    error: data.first().map_or(ApplicationError::Unspecified, |code| {
+                        ApplicationError::from(*code)
+                    }),
+                    header: Some(header),
+                })
+            }
+            ControlInformation::SendAlarmStatus => Ok(UserDataBlock::Alarm {
+                alarm_status: *data.get(1).ok_or(ApplicationLayerError::InsufficientData)?,
+                header: None,
+            }),
+            ControlInformation::SendAlarmStatusWithShortHeader
+            | ControlInformation::SendAlarmStatusWithLongHeader => {
+                let (header, data) = TransportHeader::parse(
+                    data.get(1..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                    control_information == ControlInformation::SendAlarmStatusWithLongHeader,
+                )?;
+                Ok(UserDataBlock::Alarm {
+                    alarm_status: *data
+                        .first()
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                    header: Some(header),
+                })
             }
-            ControlInformation::SendData => todo!(),
-            ControlInformation::SelectSlave => todo!(),
-            ControlInformation::SynchronizeSlave => todo!(),
-            ControlInformation::SetBaudRate300 => todo!(),
-            ControlInformation::SetBaudRate600 => todo!(),
-            ControlInformation::SetBaudRate1200 => todo!(),
-            ControlInformation::SetBaudRate2400 => todo!(),
-            ControlInformation::SetBaudRate4800 => todo!(),
-            ControlInformation::SetBaudRate9600 => todo!(),
-            ControlInformation::SetBaudRate19200 => todo!(),
-            ControlInformation::SetBaudRate38400 => todo!(),
-            ControlInformation::OutputRAMContent => todo!(),
-            ControlInformation::WriteRAMContent => todo!(),
-            ControlInformation::StartCalibrationTestMode => todo!(),
-            ControlInformation::ReadEEPROM => todo!(),
-            ControlInformation::StartSoftwareTest => todo!(),
-            ControlInformation::HashProcedure(_) => todo!(),
-            ControlInformation::SendErrorStatus => todo!(),
-            ControlInformation::SendAlarmStatus => todo!(),
             ControlInformation::ResponseWithVariableDataStructure { lsb_order } => {
+                let header = data
+                    .get(1..)
+                    .ok_or(ApplicationLayerError::InsufficientData)?;
+                Ok(UserDataBlock::VariableDataStructure {
+                    fixed_data_header: FixedDataHeader::parse(header, lsb_order)?,
+                    variable_data_block: data
+                        .get(1 + FixedDataHeader::LENGTH..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                })
+            }
+            ControlInformation::ResponseWithShortHeader
+            | ControlInformation::ResponseSelectedApplication => {
+                let header = data
+                    .get(1..)
+                    .ok_or(ApplicationLayerError::InsufficientData)?;
+                Ok(UserDataBlock::VariableDataStructureWithShortHeader {
+                    short_header: ShortHeader::parse(header)?,
+                    variable_data_block: data
+                        .get(1 + ShortHeader::LENGTH..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                })
+            }
+            ControlInformation::ResponseWithoutHeader => {
+                Ok(UserDataBlock::VariableDataStructureWithoutHeader {
+                    variable_data_block: data
+                        .get(1..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                })
+            }
+            ControlInformation::FormatFrame => {
                 let mut iter = data.iter().skip(1);
-                let mut identification_number_bytes = [
+                let length = *iter.next().ok_or(ApplicationLayerError::InsufficientData)?;
+                let format_signature = u16::from_le_bytes([
                     *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
                     *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+                ]);
+                Ok(UserDataBlock::FormatFrame {
+                    format_signature,
+                    headers: data
+                        .get(4..4 + usize::from(length))
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                })
+            }
+            ControlInformation::CompactFrame => {
+                let mut iter = data.iter().skip(1);
+                let format_signature = u16::from_le_bytes([
                     *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
                     *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                ];
-                if lsb_order {
-                    identification_number_bytes.reverse();
-                }
-
-                Ok(UserDataBlock::VariableDataStructure {
-                    fixed_data_header: FixedDataHeader {
-                        identification_number: IdentificationNumber::from_bcd_hex_digits(
-                            identification_number_bytes,
-                        )?,
-                        manufacturer: ManufacturerCode::from_id(u16::from_le_bytes([
-                            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                        ])),
-                        version: *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                        medium: MeasuredMedium::new(
-                            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                        )
-                        .medium,
-                        access_number: *iter
-                            .next()
-                            .ok_or(ApplicationLayerError::InsufficientData)?,
-                        status: StatusField::from_bits_truncate(
-                            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                        ),
-                        signature: u16::from_le_bytes([
-                            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                            *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
-                        ]),
-                        lsb_order,
-                    },
-                    variable_data_block: data
-                        .get(13..data.len())
+                ]);
+                let data_crc = u16::from_le_bytes([
+                    *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+                    *iter.next().ok_or(ApplicationLayerError::InsufficientData)?,
+                ]);
+                Ok(UserDataBlock::CompactFrame {
+                    format_signature,
+                    data_crc,
+                    data: data
+                        .get(5..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                })
+            }
+            ControlInformation::SendDataWithShortHeader
+            | ControlInformation::SendDataWithLongHeader
+            | ControlInformation::RequestSelectedApplicationWithLongHeader
+            | ControlInformation::TimeSynchronization => {
+                let (header, variable_data_block) = TransportHeader::parse(
+                    data.get(1..)
                         .ok_or(ApplicationLayerError::InsufficientData)?,
+                    control_information != ControlInformation::SendDataWithShortHeader,
+                )?;
+                Ok(UserDataBlock::SendDataWithHeader {
+                    header,
+                    variable_data_block,
                 })
             }
+            ControlInformation::TransportLayerToMeter
+            | ControlInformation::TransportLayerShortHeader
+            | ControlInformation::TransportLayerLongHeader => {
+                let (header, _) = TransportHeader::parse(
+                    data.get(1..)
+                        .ok_or(ApplicationLayerError::InsufficientData)?,
+                    control_information != ControlInformation::TransportLayerShortHeader,
+                )?;
+                Ok(UserDataBlock::TransportLayer { header })
+            }
             ControlInformation::ResponseWithFixedDataStructure => {
                 let mut iter = data.iter().skip(1);
                 let identification_number = IdentificationNumber::from_bcd_hex_digits([
@@ -797,7 +1409,7 @@ mod tests {
         );
         assert_eq!(
             ControlInformation::from(0x90),
-            Ok(ControlInformation::HashProcedure(0,))
+            Ok(ControlInformation::AuthenticationAndFragmentationLayer)
         );
         assert_eq!(
             ControlInformation::from(0x91),
@@ -813,7 +1425,366 @@ mod tests {
         assert_eq!(
             result,
             Ok(UserDataBlock::ResetAtApplicationLevel {
-                subcode: ApplicationResetSubcode::All(0x10)
+                subcode: ApplicationResetSubcode::All(0x10),
+                header: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_send_data() {
+        use crate::user_data::value_information::ValueLabel;
+
+        // Set the slave to primary address 8 | 68 06 06 68 | 53 FE 51 | 01 7A 08 | 25 16
+        let data = [0x51, 0x01, 0x7A, 0x08];
+        let result = UserDataBlock::try_from(data.as_slice());
+        assert_eq!(
+            result,
+            Ok(UserDataBlock::SendData {
+                variable_data_block: &[0x01, 0x7A, 0x08]
+            })
+        );
+
+        if let Ok(UserDataBlock::SendData {
+            variable_data_block,
+        }) = result
+        {
+            let records: Vec<_> = DataRecords::from(variable_data_block).flatten().collect();
+            assert_eq!(records.len(), 1);
+            let record = records.first().unwrap();
+            assert_eq!(
+                record.data.value,
+                Some(data_information::DataType::Number(8.0))
+            );
+            assert!(record
+                .data_record_header
+                .processed_data_record_header
+                .value_information
+                .as_ref()
+                .unwrap()
+                .labels
+                .contains(&ValueLabel::Address));
+        }
+    }
+
+    #[test]
+    fn test_select_slave() {
+        // 68 0B 0B 68 | 53 FD 52 | 78 56 34 12 | FF FF | FF | FF | CS 16
+        let data = [0x52, 0x78, 0x56, 0xFF, 0xFF, 0xE6, 0x1E, 0xFF, 0x07];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::SelectSlave {
+                secondary_address: SecondaryAddress::new(0xFFFF5678, 0x1EE6, 0xFF, 0x07)
+            })
+        );
+        assert_eq!(
+            UserDataBlock::try_from(&data[..5]),
+            Err(ApplicationLayerError::InsufficientData)
+        );
+    }
+
+    #[test]
+    fn test_user_data_behind_afl() {
+        let data = [
+            0x90, 0x0F, 0x00, 0x2C, 0x25, 0x12, 0x00, 0x00, 0x00, 0x02, 0xAF, 0xBD, 0xF9, 0x45,
+            0x15, 0x97, 0x35, 0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2B, 0x00,
+            0x00, 0x00, 0x01, 0x13, 0x05,
+        ];
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert_eq!(fixed_data_header.access_number, 0x2B);
+        assert_eq!(variable_data_block, &[0x01, 0x13, 0x05]);
+
+        // first of several fragments
+        let mut fragment = data;
+        fragment[3] = 0x6C;
+        assert_eq!(
+            UserDataBlock::try_from(fragment.as_slice()),
+            Err(ApplicationLayerError::FragmentedMessage)
+        );
+    }
+
+    #[test]
+    fn test_transport_layer_headers() {
+        let data = [0x7A, 0x2B, 0x00, 0x00, 0x00, 0x01, 0x13, 0x05];
+        let user_data = UserDataBlock::try_from(data.as_slice());
+        let short_header = ShortHeader {
+            access_number: 0x2B,
+            status: StatusField::from_bits_truncate(0x00),
+            configuration_field: ConfigurationField::from(0x0000),
+        };
+        assert_eq!(
+            user_data,
+            Ok(UserDataBlock::VariableDataStructureWithShortHeader {
+                short_header,
+                variable_data_block: &[0x01, 0x13, 0x05],
+            })
+        );
+        assert_eq!(
+            user_data
+                .as_ref()
+                .ok()
+                .and_then(UserDataBlock::short_header),
+            Some(short_header)
+        );
+
+        let data = [0x78, 0x01, 0x13, 0x05];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()).map(|block| block.variable_data_block()),
+            Ok(Some([0x01, 0x13, 0x05].as_slice()))
+        );
+
+        let data = [
+            0x5B, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2B, 0x00, 0x00, 0x00, 0x01,
+            0x13, 0x05,
+        ];
+        let Ok(UserDataBlock::SendDataWithHeader {
+            header: TransportHeader::Long(fixed_data_header),
+            variable_data_block,
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected data with a long header");
+        };
+        assert_eq!(
+            fixed_data_header.identification_number,
+            IdentificationNumber { number: 12345678 }
+        );
+        assert_eq!(variable_data_block, &[0x01, 0x13, 0x05]);
+
+        let data = [0x8A, 0x2B, 0x00, 0x00, 0x00];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::TransportLayer {
+                header: TransportHeader::Short(short_header)
+            })
+        );
+        assert_eq!(
+            UserDataBlock::try_from(&data[..4]),
+            Err(ApplicationLayerError::InsufficientData)
+        );
+    }
+
+    #[test]
+    fn test_format_and_compact_frame() {
+        let data = [0x69, 0x04, 0x34, 0x12, 0x04, 0x13, 0x02, 0x5A];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::FormatFrame {
+                format_signature: 0x1234,
+                headers: &[0x04, 0x13, 0x02, 0x5A],
+            })
+        );
+        assert_eq!(
+            UserDataBlock::try_from(&data[..7]),
+            Err(ApplicationLayerError::InsufficientData)
+        );
+
+        let data = [0x79, 0x34, 0x12, 0x78, 0x56, 0x01, 0x02];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::CompactFrame {
+                format_signature: 0x1234,
+                data_crc: 0x5678,
+                data: &[0x01, 0x02],
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_and_alarm_status() {
+        let data = [0x70, 0x08];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::ApplicationError {
+                error: ApplicationError::ApplicationBusy,
+                header: None,
+            })
+        );
+        assert_eq!(
+            UserDataBlock::try_from(&data[..1]),
+            Ok(UserDataBlock::ApplicationError {
+                error: ApplicationError::Unspecified,
+                header: None,
+            })
+        );
+        assert_eq!(
+            ApplicationError::from(0x07),
+            ApplicationError::Reserved(0x07)
+        );
+
+        let data = [0x71, 0x05];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::Alarm {
+                alarm_status: 0x05,
+                header: None,
+            })
+        );
+        assert_eq!(
+            UserDataBlock::try_from(&data[..1]),
+            Err(ApplicationLayerError::InsufficientData)
+        );
+    }
+
+    #[test]
+    fn test_error_and_alarm_status_with_header() {
+        let short_header = ShortHeader {
+            access_number: 0x2B,
+            status: StatusField::from_bits_truncate(0x00),
+            configuration_field: ConfigurationField::from(0x0000),
+        };
+        let data = [0x6E, 0x2B, 0x00, 0x00, 0x00, 0x08];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::ApplicationError {
+                error: ApplicationError::ApplicationBusy,
+                header: Some(TransportHeader::Short(short_header)),
+            })
+        );
+
+        let data = [
+            0x6F, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2B, 0x00, 0x00, 0x00,
+        ];
+        let Ok(UserDataBlock::ApplicationError {
+            error: ApplicationError::Unspecified,
+            header: Some(TransportHeader::Long(fixed_data_header)),
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected an application error with a long header");
+        };
+        assert_eq!(fixed_data_header.manufacturer_id, 0x1EE6);
+
+        let data = [0x74, 0x2B, 0x00, 0x00, 0x00, 0x05];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::Alarm {
+                alarm_status: 0x05,
+                header: Some(TransportHeader::Short(short_header)),
+            })
+        );
+        assert_eq!(
+            UserDataBlock::try_from(&data[..5]),
+            Err(ApplicationLayerError::InsufficientData)
+        );
+
+        let data = [
+            0x75, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2B, 0x00, 0x00, 0x00, 0x05,
+        ];
+        let Ok(UserDataBlock::Alarm {
+            alarm_status: 0x05,
+            header: Some(TransportHeader::Long(fixed_data_header)),
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected an alarm with a long header");
+        };
+        assert_eq!(
+            fixed_data_header.identification_number,
+            IdentificationNumber { number: 12345678 }
+        );
+    }
+
+    #[test]
+    fn test_commands_with_long_header() {
+        let header = [
+            0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x2B, 0x00, 0x00, 0x00,
+        ];
+
+        let mut data = [0u8; 14];
+        data[0] = 0x53;
+        data[1..13].copy_from_slice(&header);
+        data[13] = 0x01;
+        let Ok(UserDataBlock::ResetAtApplicationLevel {
+            subcode: ApplicationResetSubcode::UserData(0x01),
+            header: Some(TransportHeader::Long(_)),
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected an application reset with a long header");
+        };
+
+        for ci in [0x55, 0x6C, 0x6D] {
+            data[0] = ci;
+            let Ok(UserDataBlock::SendDataWithHeader {
+                header: TransportHeader::Long(_),
+                variable_data_block,
+            }) = UserDataBlock::try_from(data.as_slice())
+            else {
+                panic!("expected data with a long header");
+            };
+            assert_eq!(variable_data_block, &[0x01]);
+        }
+        assert_eq!(
+            UserDataBlock::try_from(&data[..12]),
+            Err(ApplicationLayerError::InsufficientData)
+        );
+    }
+
+    #[test]
+    fn test_selected_application_response() {
+        let data = [0x7B, 0x2B, 0x00, 0x00, 0x00, 0x01];
+        let Ok(UserDataBlock::VariableDataStructureWithShortHeader {
+            short_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a response with a short header");
+        };
+        assert_eq!(short_header.access_number, 0x2B);
+        assert_eq!(variable_data_block, &[0x01]);
+    }
+
+    #[test]
+    fn test_extended_link_layer() {
+        let data = [
+            0x8C, 0x20, 0x2A, 0x7A, 0x2B, 0x00, 0x00, 0x00, 0x01, 0x13, 0x05,
+        ];
+        let Ok(UserDataBlock::VariableDataStructureWithShortHeader {
+            short_header,
+            variable_data_block,
+        }) = UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a response with a short header");
+        };
+        assert_eq!(short_header.access_number, 0x2B);
+        assert_eq!(variable_data_block, &[0x01, 0x13, 0x05]);
+
+        let data = [
+            0x8E, 0x00, 0x2A, 0xE6, 0x1E, 0x78, 0x56, 0x34, 0x12, 0x01, 0x07, 0x8A, 0x2B, 0x00,
+            0x00, 0x00,
+        ];
+        assert!(matches!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::TransportLayer {
+                header: TransportHeader::Short(_)
+            })
+        ));
+
+        // session number with encryption mode 1
+        let data = [
+            0x8D, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x7A, 0x2B, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Err(ApplicationLayerError::UnsupportedControlInformation { byte: 0x8D })
+        );
+        assert_eq!(
+            UserDataBlock::try_from(&data[..5]),
+            Err(ApplicationLayerError::InsufficientData)
+        );
+    }
+
+    #[test]
+    fn test_reset_without_subcode() {
+        let data = [0x50];
+        assert_eq!(
+            UserDataBlock::try_from(data.as_slice()),
+            Ok(UserDataBlock::ResetAtApplicationLevel {
+                subcode: ApplicationResetSubcode::All(0x00),
+                header: None,
             })
         );
     }
@@ -917,9 +1888,7 @@ mod tests {
                     );
 
                     let mut data_records =
-                        DataRecords::try_from((variable_data_block, &fixed_data_header))
-                            .unwrap()
-                            .flatten();
+                        DataRecords::from((variable_data_block, &fixed_data_header)).flatten();
                     data_records.next().unwrap();
                     assert_eq!(data_records.next().unwrap().data.value, data_record_value);
                 } else {
@@ -960,8 +1929,7 @@ mod tests {
             } = user_data_block
             {
                 let mut data_records: Vec<_> =
-                    DataRecords::try_from((variable_data_block, &fixed_data_header))
-                        .unwrap()
+                    DataRecords::from((variable_data_block, &fixed_data_header))
                         .flatten()
                         .collect();
 
@@ -1014,15 +1982,15 @@ mod tests {
                 variable_data_block,
             } = user_data_block
             {
-                let mut data_records: Vec<DataRecord> =
-                    DataRecords::try_from((variable_data_block, &fixed_data_header))
-                        .unwrap()
+                let data_records: Vec<DataRecord> =
+                    DataRecords::from((variable_data_block, &fixed_data_header))
                         .flatten()
                         .collect();
 
-                assert_eq!(data_records.len(), 24);
+                // the last record holds the manufacturer specific data after the DIF 0x1F
+                assert_eq!(data_records.len(), 25);
 
-                for data_record in data_records {
+                for data_record in data_records.iter().take(24) {
                     let labels = data_record
                         .data_record_header
                         .processed_data_record_header
@@ -0,0 +1,316 @@
+//! Secondary address of a slave as used for selection (EN 13757-3, CI 0x52)
+#[cfg(feature = "std")]
+use std::fmt;
+
+use super::{ApplicationLayerError, FixedDataHeader, ManufacturerCode};
+
+/// A secondary address is made of the identification number, the manufacturer, the version
+/// and the medium of a slave. Any BCD nibble of the identification number set to `0xF`, a
+/// manufacturer of `0xFFFF` and a version or medium of `0xFF` act as wildcards.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SecondaryAddress {
+    /// BCD digits of the identification number, e.g. `0x1234FFFF` for `1234****`
+    pub identification_number: u32,
+    pub manufacturer: u16,
+    pub version: u8,
+    pub medium: u8,
+}
+
+impl SecondaryAddress {
+    /// Matches every slave on the bus
+    pub const WILDCARD: Self = Self {
+        identification_number: 0xFFFF_FFFF,
+        manufacturer: 0xFFFF,
+        version: 0xFF,
+        medium: 0xFF,
+    };
+
+    #[must_use]
+    pub const fn new(
+        identification_number: u32,
+        manufacturer: u16,
+        version: u8,
+        medium: u8,
+    ) -> Self {
+        Self {
+            identification_number,
+            manufacturer,
+            version,
+            medium,
+        }
+    }
+
+    /// Reads the address in the order it is sent on the wire
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; 8]) -> Self {
+        Self {
+            identification_number: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            manufacturer: u16::from_le_bytes([bytes[4], bytes[5]]),
+            version: bytes[6],
+            medium: bytes[7],
+        }
+    }
+
+    /// Writes the address in the order it is sent on the wire
+    #[must_use]
+    pub const fn to_bytes(&self) -> [u8; 8] {
+        let id = self.identification_number.to_le_bytes();
+        let manufacturer = self.manufacturer.to_le_bytes();
+        [
+            id[0],
+            id[1],
+            id[2],
+            id[3],
+            manufacturer[0],
+            manufacturer[1],
+            self.version,
+            self.medium,
+        ]
+    }
+
+    /// Returns true if any of the fields contains a wildcard
+    #[must_use]
+    pub const fn has_wildcards(&self) -> bool {
+        let mut nibble = 0;
+        while nibble < 8 {
+            if (self.identification_number >> (nibble * 4)) & 0xF == 0xF {
+                return true;
+            }
+            nibble += 1;
+        }
+        self.manufacturer == 0xFFFF || self.version == 0xFF || self.medium == 0xFF
+    }
+
+    /// Checks if `address` is selected by `self`, wildcards are only honoured in `self`
+    #[must_use]
+    pub const fn matches(&self, address: &SecondaryAddress) -> bool {
+        let mut nibble = 0;
+        while nibble < 8 {
+            let pattern = (self.identification_number >> (nibble * 4)) & 0xF;
+            let digit = (address.identification_number >> (nibble * 4)) & 0xF;
+            if pattern != 0xF && pattern != digit {
+                return false;
+            }
+            nibble += 1;
+        }
+        (self.manufacturer == 0xFFFF || self.manufacturer == address.manufacturer)
+            && (self.version == 0xFF || self.version == address.version)
+            && (self.medium == 0xFF || self.medium == address.medium)
+    }
+}
+
+impl From<&FixedDataHeader> for SecondaryAddress {
+    fn from(header: &FixedDataHeader) -> Self {
+        let mut number = header.identification_number.number;
+        let mut identification_number = 0u32;
+        for nibble in 0..8 {
+            identification_number |= (number % 10) << (nibble * 4);
+            number /= 10;
+        }
+        Self {
+            identification_number,
+            manufacturer: header.manufacturer_id,
+            version: header.version,
+            medium: header.medium_byte,
+        }
+    }
+}
+
+fn parse_hex_byte(text: &str) -> Result<u8, ApplicationLayerError> {
+    if text == "**" {
+        return Ok(0xFF);
+    }
+    if text.len() != 2 {
+        return Err(ApplicationLayerError::InvalidSecondaryAddress);
+    }
+    u8::from_str_radix(text, 16).map_err(|_| ApplicationLayerError::InvalidSecondaryAddress)
+}
+
+/// Parses the `12345678-GWF-01-07` notation, wildcards are written as `*` (or `F` within the
+/// identification number, `FF` for version and medium)
+impl core::str::FromStr for SecondaryAddress {
+    type Err = ApplicationLayerError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.trim().split('-');
+        let mut next = || {
+            parts
+                .next()
+                .ok_or(ApplicationLayerError::InvalidSecondaryAddress)
+        };
+        let (id, manufacturer, version, medium) = (next()?, next()?, next()?, next()?);
+        if parts.next().is_some() || id.len() != 8 || manufacturer.len() != 3 {
+            return Err(ApplicationLayerError::InvalidSecondaryAddress);
+        }
+
+        let mut identification_number = 0u32;
+        for character in id.chars() {
+            let nibble = match character {
+                '0'..='9' => character as u32 - '0' as u32,
+                'F' | 'f' | '*' => 0xF,
+                _ => return Err(ApplicationLayerError::InvalidSecondaryAddress),
+            };
+            identification_number = (identification_number << 4) | nibble;
+        }
+
+        let manufacturer = if manufacturer == "***" {
+            0xFFFF
+        } else {
+            let mut code = ['\0'; 3];
+            for (slot, character) in code.iter_mut().zip(manufacturer.chars()) {
+                if !character.is_ascii_alphabetic() {
+                    return Err(ApplicationLayerError::InvalidSecondaryAddress);
+                }
+                *slot = character.to_ascii_uppercase();
+            }
+            ManufacturerCode { code }.to_id()
+        };
+
+        Ok(Self {
+            identification_number,
+            manufacturer,
+            version: parse_hex_byte(version)?,
+            medium: parse_hex_byte(medium)?,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for SecondaryAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for nibble in (0..8).rev() {
+            match (self.identification_number >> (nibble * 4)) & 0xF {
+                0xF => write!(f, "*")?,
+                digit => write!(f, "{:X}", digit)?,
+            }
+        }
+        match self.manufacturer {
+            0xFFFF => write!(f, "-***")?,
+            id => match ManufacturerCode::from_id(id) {
+                Ok(code) => write!(f, "-{}", code)?,
+                Err(_) => write!(f, "-{:04X}", id)?,
+            },
+        }
+        for byte in [self.version, self.medium] {
+            match byte {
+                0xFF => write!(f, "-**")?,
+                byte => write!(f, "-{:02X}", byte)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_secondary_address_from_str() {
+        let address = SecondaryAddress::from_str("12345678-GWF-01-07");
+        assert_eq!(
+            address,
+            Ok(SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07))
+        );
+        assert_eq!(
+            SecondaryAddress::from_str("1234****-***-**-07"),
+            Ok(SecondaryAddress::new(0x1234FFFF, 0xFFFF, 0xFF, 0x07))
+        );
+        assert_eq!(
+            SecondaryAddress::from_str("1234FFFF-gwf-FF-07"),
+            Ok(SecondaryAddress::new(0x1234FFFF, 0x1EE6, 0xFF, 0x07))
+        );
+        assert_eq!(
+            SecondaryAddress::from_str("1234567-GWF-01-07"),
+            Err(ApplicationLayerError::InvalidSecondaryAddress)
+        );
+        assert_eq!(
+            SecondaryAddress::from_str("12345678-G1F-01-07"),
+            Err(ApplicationLayerError::InvalidSecondaryAddress)
+        );
+        assert_eq!(
+            SecondaryAddress::from_str("12345678-GWF-01"),
+            Err(ApplicationLayerError::InvalidSecondaryAddress)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_secondary_address_display() {
+        let address = SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07);
+        assert_eq!(address.to_string(), "12345678-GWF-01-07");
+        assert_eq!(
+            SecondaryAddress::new(0x1234FFFF, 0xFFFF, 0xFF, 0x07).to_string(),
+            "1234****-***-**-07"
+        );
+        assert_eq!(
+            SecondaryAddress::from_str(&address.to_string()),
+            Ok(address)
+        );
+    }
+
+    #[test]
+    fn test_secondary_address_bytes() {
+        let bytes = [0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07];
+        let address = SecondaryAddress::from_bytes(bytes);
+        assert_eq!(
+            address,
+            SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07)
+        );
+        assert_eq!(address.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_secondary_address_matches() {
+        let address = SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07);
+        assert!(!address.has_wildcards());
+        assert!(address.matches(&address));
+        assert!(SecondaryAddress::WILDCARD.matches(&address));
+        assert!(SecondaryAddress::new(0x1234FFFF, 0xFFFF, 0xFF, 0x07).matches(&address));
+        assert!(SecondaryAddress::new(0xF2F4F6F8, 0x1EE6, 0x01, 0xFF).matches(&address));
+        assert!(!SecondaryAddress::new(0x1235FFFF, 0xFFFF, 0xFF, 0xFF).matches(&address));
+        assert!(!SecondaryAddress::new(0xFFFFFFFF, 0xFFFF, 0x02, 0xFF).matches(&address));
+        // wildcards are only honoured in the pattern
+        assert!(!address.matches(&SecondaryAddress::WILDCARD));
+    }
+
+    #[test]
+    fn test_secondary_address_from_fixed_data_header() {
+        let data = [
+            0x72, 0x78, 0x56, 0x34, 0x12, 0xE6, 0x1E, 0x01, 0x07, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let Ok(super::super::UserDataBlock::VariableDataStructure {
+            fixed_data_header, ..
+        }) = super::super::UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert_eq!(
+            SecondaryAddress::from(&fixed_data_header),
+            SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07)
+        );
+    }
+
+    #[test]
+    fn test_secondary_address_keeps_raw_header_bytes() {
+        // medium 0x1B has no variant of its own and 0x0000 is no valid manufacturer
+        let data = [
+            0x72, 0x78, 0x56, 0x34, 0x12, 0x00, 0x00, 0x01, 0x1B, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let Ok(super::super::UserDataBlock::VariableDataStructure {
+            fixed_data_header, ..
+        }) = super::super::UserDataBlock::try_from(data.as_slice())
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert!(fixed_data_header.manufacturer.is_err());
+        let address = SecondaryAddress::from(&fixed_data_header);
+        assert_eq!(
+            address.to_bytes(),
+            [0x78, 0x56, 0x34, 0x12, 0x00, 0x00, 0x01, 0x1B]
+        );
+    }
+}
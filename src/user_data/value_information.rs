@@ -54,26 +54,15 @@ impl TryFrom<&[u8]> for ValueInformationBlock {
                 let vife_data = *data.get(offset).ok_or(DataInformationError::DataTooShort)?;
                 let current_vife = ValueInformationFieldExtension {
                     data: vife_data,
-                    coding: match (offset, vife_data) {
-                        (0, 0xFB) => ValueInformationFieldExtensionCoding::MainVIFCodeExtension,
-                        (0, 0xFC) => {
-                            ValueInformationFieldExtensionCoding::AlternateVIFCodeExtension
-                        }
-                        (0, 0xEF) => {
-                            ValueInformationFieldExtensionCoding::ReservedAlternateVIFCodeExtension
-                        }
-                        _ => ValueInformationFieldExtensionCoding::ComninableOrthogonalVIFECodeExtension,
-                    },
+                    coding: ValueInformationFieldExtensionCoding::following(&vif, vife.last()),
                 };
                 let has_extension = current_vife.has_extension();
-                vife.push(current_vife);
+                vife.try_push(current_vife)
+                    .map_err(|_| DataInformationError::InvalidValueInformation)?;
                 offset += 1;
                 if !has_extension {
                     break;
                 }
-                if vife.len() > MAX_VIFE_RECORDS {
-                    return Err(DataInformationError::InvalidValueInformation);
-                }
             }
             if standard_plaintex_vib && vif.value_information_contains_ascii() {
                 plaintext_vife = Some(extract_plaintext_vife(
@@ -135,15 +124,12 @@ pub struct ValueInformationFieldExtension {
 impl From<&ValueInformationField> for ValueInformationCoding {
     fn from(value_information: &ValueInformationField) -> Self {
         match value_information.data {
-            0x00..=0x7B | 0x80..=0xFA => Self::Primary,
+            0x00..=0x7A | 0x80..=0xFA => Self::Primary,
             0x7C | 0xFC => Self::PlainText,
-            0xFD => Self::MainVIFExtension,
-            0xFB => Self::AlternateVIFExtension,
-            0x7E => Self::ManufacturerSpecific,
-            0xFE => Self::ManufacturerSpecific,
-            0x7F => Self::ManufacturerSpecific,
-            0xFF => Self::ManufacturerSpecific,
-            _ => unreachable!("Invalid value information: {:X}", value_information.data),
+            0x7D | 0xFD => Self::MainVIFExtension,
+            0x7B | 0xFB => Self::AlternateVIFExtension,
+            0x7E | 0xFE => Self::AnyVIF,
+            0x7F | 0xFF => Self::ManufacturerSpecific,
         }
     }
 }
@@ -167,16 +153,55 @@ pub enum ValueInformationCoding {
     PlainText,
     MainVIFExtension,
     AlternateVIFExtension,
+    /// Matches any VIF, used when selecting records for readout
+    AnyVIF,
     ManufacturerSpecific,
 }
+/// The table a VIFE is looked up in
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ValueInformationFieldExtensionCoding {
+    /// Code of the 0xFD table, following the VIF 0xFD or the code 0x7D of that table
     MainVIFCodeExtension,
+    /// Code of the 0xFB table, following the VIF 0xFB
     AlternateVIFCodeExtension,
+    /// Code of the table reserved for the VIF 0xEF
     ReservedAlternateVIFCodeExtension,
     ComninableOrthogonalVIFECodeExtension,
+    /// Code of the second level table, following the combinable orthogonal VIFE 0xFC
+    ExtensionOfCombinableOrthogonalVIFECode,
+    /// Following the VIF 0xFF or the combinable orthogonal VIFE 0xFF
+    ManufacturerSpecificVIFECode,
+}
+
+impl ValueInformationFieldExtensionCoding {
+    /// The coding of the VIFE following `vif` and the VIFE `previous`, if any
+    const fn following(
+        vif: &ValueInformationField,
+        previous: Option<&ValueInformationFieldExtension>,
+    ) -> Self {
+        let Some(previous) = previous else {
+            return match vif.data {
+                0xFD => Self::MainVIFCodeExtension,
+                0xFB => Self::AlternateVIFCodeExtension,
+                0xEF => Self::ReservedAlternateVIFCodeExtension,
+                0xFF => Self::ManufacturerSpecificVIFECode,
+                _ => Self::ComninableOrthogonalVIFECodeExtension,
+            };
+        };
+        match (&previous.coding, previous.data & 0x7F) {
+            (Self::ManufacturerSpecificVIFECode, _) => Self::ManufacturerSpecificVIFECode,
+            (Self::MainVIFCodeExtension, 0x7D) => Self::MainVIFCodeExtension,
+            (Self::ComninableOrthogonalVIFECodeExtension, 0x7C) => {
+                Self::ExtensionOfCombinableOrthogonalVIFECode
+            }
+            (Self::ComninableOrthogonalVIFECodeExtension, 0x7F) => {
+                Self::ManufacturerSpecificVIFECode
+            }
+            _ => Self::ComninableOrthogonalVIFECodeExtension,
+        }
+    }
 }
 
 impl ValueInformationBlock {
@@ -192,6 +217,21 @@ impl ValueInformationBlock {
         }
         size
     }
+
+    /// True if the VIF or one of the VIFEs is manufacturer specific
+    #[must_use]
+    pub fn is_manufacturer_specific(&self) -> bool {
+        ValueInformationCoding::from(&self.value_information)
+            == ValueInformationCoding::ManufacturerSpecific
+            || self
+                .value_information_extension
+                .as_ref()
+                .is_some_and(|extension| {
+                    extension.iter().any(|vife| {
+                        vife.coding == ValueInformationFieldExtensionCoding::ManufacturerSpecificVIFECode
+                    })
+                })
+    }
 }
 
 impl TryFrom<&ValueInformationBlock> for ValueInformation {
@@ -229,12 +269,14 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                         decimal_scale_exponent =
                             (value_information_block.value_information.data & 0b111) as isize - 3;
                     }
-                    0x20 | 0x24 => {
-                        units.push(unit!(Second));
+                    0x20..=0x23 => {
+                        units.push(time_unit(value_information_block.value_information.data));
+                        labels.push(ValueLabel::OnTime);
+                    }
+                    0x24..=0x27 => {
+                        units.push(time_unit(value_information_block.value_information.data));
+                        labels.push(ValueLabel::OperatingTime);
                     }
-                    0x21 | 0x25 => units.push(unit!(Meter)),
-                    0x22 | 0x26 => units.push(unit!(Hour)),
-                    0x23 | 0x27 => units.push(unit!(Day)),
                     0x28..=0x2F => {
                         units.push(unit!(Watt));
                         decimal_scale_exponent +=
@@ -265,46 +307,57 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                             (value_information_block.value_information.data & 0b111) as isize - 9;
                     }
                     0x50..=0x57 => {
-                        units.push(unit!(Kilogram ^ 3));
+                        units.push(unit!(Kilogram));
                         units.push(unit!(Hour ^ -1));
                         decimal_scale_exponent +=
                             (value_information_block.value_information.data & 0b111) as isize - 3;
                     }
-                    0x58..=0x5F | 0x64..=0x67 => {
+                    0x58..=0x5B => {
+                        units.push(unit!(Celsius));
+                        labels.push(ValueLabel::FlowTemperature);
+                        decimal_scale_exponent +=
+                            (value_information_block.value_information.data & 0b11) as isize - 3;
+                    }
+                    0x5C..=0x5F => {
                         units.push(unit!(Celsius));
+                        labels.push(ValueLabel::ReturnTemperature);
+                        decimal_scale_exponent +=
+                            (value_information_block.value_information.data & 0b11) as isize - 3;
+                    }
+                    0x64..=0x67 => {
+                        units.push(unit!(Celsius));
+                        labels.push(ValueLabel::ExternalTemperature);
                         decimal_scale_exponent +=
                             (value_information_block.value_information.data & 0b11) as isize - 3;
                     }
                     0x60..=0x63 => {
                         units.push(unit!(Kelvin));
+                        labels.push(ValueLabel::TemperatureDifference);
                         decimal_scale_exponent +=
                             (value_information_block.value_information.data & 0b11) as isize - 3;
                     }
                     0x68..=0x6B => {
                         units.push(unit!(Bar));
+                        labels.push(ValueLabel::Pressure);
                         decimal_scale_exponent +=
                             (value_information_block.value_information.data & 0b11) as isize - 3;
                     }
                     0x6C => labels.push(ValueLabel::Date),
                     0x6D => labels.push(ValueLabel::DateTime),
                     0x6E => labels.push(ValueLabel::DimensionlessHCA),
-                    0x70..=0x73 => labels.push(ValueLabel::AveragingDuration),
-                    0x74..=0x77 => labels.push(ValueLabel::ActualityDuration),
+                    0x70..=0x73 => {
+                        units.push(time_unit(value_information_block.value_information.data));
+                        labels.push(ValueLabel::AveragingDuration);
+                    }
+                    0x74..=0x77 => {
+                        units.push(time_unit(value_information_block.value_information.data));
+                        labels.push(ValueLabel::ActualityDuration);
+                    }
                     0x78 => labels.push(ValueLabel::FabricationNumber),
                     0x79 => labels.push(ValueLabel::EnhancedIdentification),
                     0x7A => labels.push(ValueLabel::Address),
-                    0x7B => {}
-
-                    x => todo!("Implement the rest of the units: {:X?}", x),
+                    _ => labels.push(ValueLabel::Reserved),
                 };
-                /* consume orthogonal vife */
-                consume_orthhogonal_vife(
-                    value_information_block,
-                    &mut labels,
-                    &mut units,
-                    &mut decimal_scale_exponent,
-                    &mut decimal_offset_exponent,
-                );
             }
             ValueInformationCoding::MainVIFExtension => {
                 let vife = value_information_block
@@ -390,28 +443,46 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                         units.push(unit!(Year));
                         labels.push(ValueLabel::StorageInterval);
                     }
-                    0x30 => labels.push(ValueLabel::DimensionlessHCA),
-                    0x31 => labels.push(ValueLabel::DataContainerForWmbusProtocol),
-                    0x32 => {
+                    0x2A => labels.push(ValueLabel::OperatorSpecificData),
+                    0x2B => {
                         units.push(unit!(Second));
-                        labels.push(ValueLabel::PeriodOfNormalDataTransmition);
+                        labels.push(ValueLabel::TimePointSecond);
                     }
-                    0x33 => {
-                        units.push(unit!(Meter));
-                        labels.push(ValueLabel::PeriodOfNormalDataTransmition);
+                    0x2C..=0x2F => {
+                        units.push(time_unit(first_vife_data));
+                        labels.push(ValueLabel::DurationSinceLastReadout);
                     }
-                    0x34 => {
-                        units.push(unit!(Hour));
-                        labels.push(ValueLabel::PeriodOfNormalDataTransmition);
+                    0x30 => labels.push(ValueLabel::StartOfTariff),
+                    0x31..=0x33 => {
+                        units.push(time_unit(first_vife_data));
+                        labels.push(ValueLabel::DurationOfTariff);
                     }
-                    0x35 => {
-                        units.push(unit!(Day));
+                    0x34..=0x37 => {
+                        units.push(time_unit(first_vife_data));
+                        labels.push(ValueLabel::PeriodOfTariff);
+                    }
+                    0x38 => {
+                        units.push(unit!(Month));
+                        labels.push(ValueLabel::PeriodOfTariff);
+                    }
+                    0x39 => {
+                        units.push(unit!(Year));
+                        labels.push(ValueLabel::PeriodOfTariff);
+                    }
+                    0x3A => labels.push(ValueLabel::Dimensionless),
+                    0x3B => labels.push(ValueLabel::DataContainerForWmbusProtocol),
+                    0x3C..=0x3F => {
+                        units.push(time_unit(first_vife_data));
                         labels.push(ValueLabel::PeriodOfNormalDataTransmition);
                     }
-                    0x50..=0x5F => {
+                    0x40..=0x4F => {
                         units.push(unit!(Volt));
                         decimal_scale_exponent = (first_vife_data & 0b1111) as isize - 9;
                     }
+                    0x50..=0x5F => {
+                        units.push(unit!(Ampere));
+                        decimal_scale_exponent = (first_vife_data & 0b1111) as isize - 12;
+                    }
                     0x60 => labels.push(ValueLabel::ResetCounter),
                     0x61 => labels.push(ValueLabel::CumulationCounter),
                     0x62 => labels.push(ValueLabel::ControlSignal),
@@ -449,20 +520,20 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                         labels.push(ValueLabel::OperatingTimeBattery);
                     }
                     0x6F => {
-                        units.push(unit!(Hour));
+                        units.push(unit!(Year));
                         labels.push(ValueLabel::OperatingTimeBattery);
                     }
-                    0x70 => {
-                        units.push(unit!(Second));
-                        labels.push(ValueLabel::DateAndTimeOfBatteryChange);
-                    }
+                    0x70 => labels.push(ValueLabel::DateAndTimeOfBatteryChange),
                     0x71 => {
                         units.push(unit!(DecibelMilliWatt));
                         labels.push(ValueLabel::RFPowerLevel);
                     }
                     0x72 => labels.push(ValueLabel::DaylightSavingBeginningEndingDeviation),
                     0x73 => labels.push(ValueLabel::ListeningWindowManagementData),
-                    0x74 => labels.push(ValueLabel::RemainingBatteryLifeTime),
+                    0x74 => {
+                        units.push(unit!(Day));
+                        labels.push(ValueLabel::RemainingBatteryLifeTime);
+                    }
                     0x75 => labels.push(ValueLabel::NumberOfTimesTheMeterWasStopped),
                     0x76 => labels.push(ValueLabel::DataContainerForManufacturerSpecificProtocol),
                     0x7D => match second_vife_data.map(|s| s & 0x7F) {
@@ -496,6 +567,11 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                         units.push(mk_unit(Hour, -1));
                         populate!(@snd $($rem)*)
                     }};
+                    ($name:ident / min, $exponent:expr, $($rem:tt)*) => {{
+                        units.push(mk_unit($name, $exponent));
+                        units.push(mk_unit(Minute, -1));
+                        populate!(@snd $($rem)*)
+                    }};
                     ($name:ident * h, $exponent:expr, $($rem:tt)*) => {{
                         units.push(mk_unit($name, $exponent));
                         units.push(mk_unit(Hour, 1));
@@ -512,27 +588,37 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                     .ok_or(Self::Error::InvalidValueInformation)?;
                 let first_vife_data = vife.first().ok_or(DataInformationError::DataTooShort)?.data;
                 match first_vife_data & 0x7F {
-                    0b0 => populate!(Watt / h, 3, dec: 5, Energy),
-                    0b000_0001 => populate!(Watt / h, 3, dec: 6, Energy),
+                    0b000_0000 => populate!(Watt * h, 1, dec: 5, Energy),
+                    0b000_0001 => populate!(Watt * h, 1, dec: 6, Energy),
                     0b000_0010 => populate!(ReactiveWatt * h, 1, dec: 3, Energy),
                     0b000_0011 => populate!(ReactiveWatt * h, 1, dec: 4, Energy),
+                    0b000_0100 => populate!(ApparentWatt * h, 1, dec: 3, Energy),
+                    0b000_0101 => populate!(ApparentWatt * h, 1, dec: 4, Energy),
+                    0b000_0110 => populate!(ApparentWatt * h, 1, dec: 5, Energy),
+                    0b000_0111 => populate!(ApparentWatt * h, 1, dec: 6, Energy),
                     0b000_1000 => populate!(Joul, 1, dec: 8, Energy),
                     0b000_1001 => populate!(Joul, 1, dec: 9, Energy),
-                    0b000_1100 => populate!(Joul, 1, dec: 5, Energy),
-                    0b000_1101 => populate!(Joul, 1, dec: 6, Energy),
-                    0b000_1110 => populate!(Joul, 1, dec: 7, Energy),
-                    0b000_1111 => populate!(Joul, 1, dec: 8, Energy),
-                    0b001_0000 => populate!(Meter, 3, dec: 2),
-                    0b001_0001 => populate!(Meter, 3, dec: 3),
-                    0b001_0100 => populate!(ReactiveWatt, 1, dec: -3),
-                    0b001_0101 => populate!(ReactiveWatt, 1, dec: -2),
-                    0b001_0110 => populate!(ReactiveWatt, 1, dec: -1),
-                    0b001_0111 => populate!(ReactiveWatt, 1, dec: 0),
+                    0b000_1100 => populate!(Calorie, 1, dec: 5, Energy),
+                    0b000_1101 => populate!(Calorie, 1, dec: 6, Energy),
+                    0b000_1110 => populate!(Calorie, 1, dec: 7, Energy),
+                    0b000_1111 => populate!(Calorie, 1, dec: 8, Energy),
+                    0b001_0000 => populate!(Meter, 3, dec: 2, Volume),
+                    0b001_0001 => populate!(Meter, 3, dec: 3, Volume),
+                    0b001_0100 => populate!(ReactiveWatt, 1, dec: 0),
+                    0b001_0101 => populate!(ReactiveWatt, 1, dec: 1),
+                    0b001_0110 => populate!(ReactiveWatt, 1, dec: 2),
+                    0b001_0111 => populate!(ReactiveWatt, 1, dec: 3),
                     0b001_1000 => populate!(Tonne, 1, dec: 2),
                     0b001_1001 => populate!(Tonne, 1, dec: 3),
                     0b001_1010 => populate!(Percent, 1, dec: -1, RelativeHumidity),
-                    0b010_0000 => populate!(Feet, 3, dec: 0),
-                    0b010_0001 => populate!(Feet, 3, dec: 1),
+                    0b001_1011 => populate!(Percent, 1, dec: 0, RelativeHumidity),
+                    0b010_0000 => populate!(Feet, 3, dec: 0, Volume),
+                    0b010_0001 => populate!(Feet, 3, dec: -1, Volume),
+                    0b010_0010 => populate!(AmericanGallon, 1, dec: -1, Volume),
+                    0b010_0011 => populate!(AmericanGallon, 1, dec: 0, Volume),
+                    0b010_0100 => populate!(AmericanGallon / min, 1, dec: -3),
+                    0b010_0101 => populate!(AmericanGallon / min, 1, dec: 0),
+                    0b010_0110 => populate!(AmericanGallon / h, 1, dec: 0),
                     0b010_1000 => populate!(Watt, 1, dec: 5),
                     0b010_1001 => populate!(Watt, 1, dec: 6),
                     0b010_1010 => populate!(Degree, 1, dec: -1, PhaseUtoU),
@@ -541,12 +627,41 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                     0b010_1101 => populate!(Hertz, 1, dec: -2),
                     0b010_1110 => populate!(Hertz, 1, dec: -1),
                     0b010_1111 => populate!(Hertz, 1, dec: 0),
-                    0b011_0000 => populate!(Joul / h, 1, dec: -8),
-                    0b011_0001 => populate!(Joul / h, 1, dec: -7),
-                    0b011_0100 => populate!(ApparentWatt / h, 1, dec: 0),
-                    0b011_0101 => populate!(ApparentWatt / h, 1, dec: 1),
-                    0b011_0110 => populate!(ApparentWatt / h, 1, dec: 2),
-                    0b011_0111 => populate!(ApparentWatt / h, 1, dec: 3),
+                    0b011_0000 => populate!(Joul / h, 1, dec: 8),
+                    0b011_0001 => populate!(Joul / h, 1, dec: 9),
+                    0b011_0100 => populate!(ApparentWatt, 1, dec: 0),
+                    0b011_0101 => populate!(ApparentWatt, 1, dec: 1),
+                    0b011_0110 => populate!(ApparentWatt, 1, dec: 2),
+                    0b011_0111 => populate!(ApparentWatt, 1, dec: 3),
+                    0b101_1000 => populate!(Fahrenheit, 1, dec: -3, FlowTemperature),
+                    0b101_1001 => populate!(Fahrenheit, 1, dec: -2, FlowTemperature),
+                    0b101_1010 => populate!(Fahrenheit, 1, dec: -1, FlowTemperature),
+                    0b101_1011 => populate!(Fahrenheit, 1, dec: 0, FlowTemperature),
+                    0b101_1100 => populate!(Fahrenheit, 1, dec: -3, ReturnTemperature),
+                    0b101_1101 => populate!(Fahrenheit, 1, dec: -2, ReturnTemperature),
+                    0b101_1110 => populate!(Fahrenheit, 1, dec: -1, ReturnTemperature),
+                    0b101_1111 => populate!(Fahrenheit, 1, dec: 0, ReturnTemperature),
+                    0b110_0000 => populate!(Fahrenheit, 1, dec: -3, TemperatureDifference),
+                    0b110_0001 => populate!(Fahrenheit, 1, dec: -2, TemperatureDifference),
+                    0b110_0010 => populate!(Fahrenheit, 1, dec: -1, TemperatureDifference),
+                    0b110_0011 => populate!(Fahrenheit, 1, dec: 0, TemperatureDifference),
+                    0b110_0100 => populate!(Fahrenheit, 1, dec: -3, ExternalTemperature),
+                    0b110_0101 => populate!(Fahrenheit, 1, dec: -2, ExternalTemperature),
+                    0b110_0110 => populate!(Fahrenheit, 1, dec: -1, ExternalTemperature),
+                    0b110_0111 => populate!(Fahrenheit, 1, dec: 0, ExternalTemperature),
+                    0b110_1000 => populate!(HCAUnit, 1,dec: 0, ResultingRatingFactor),
+                    0b110_1001 => populate!(HCAUnit, 1,dec: 0, ThermalOutputRatingFactor),
+                    0b110_1010 => populate!(HCAUnit, 1,dec: 0, ThermalCouplingRatingFactorOverall),
+                    0b110_1011 => populate!(HCAUnit, 1,dec: 0, ThermalCouplingRatingRoomSide),
+                    0b110_1100 => {
+                        populate!(HCAUnit, 1,dec: 0, ThermalCouplingRatingFactorHeatingSide)
+                    }
+                    0b110_1101 => populate!(HCAUnit, 1,dec: 0, LowTemperatureRatingFactor),
+                    0b110_1110 => populate!(HCAUnit, 1,dec: 0, DisplayOutputScalingFactor),
+                    0b111_0000 => populate!(Fahrenheit, 1, dec: -3, ColdWarmTemperatureLimit),
+                    0b111_0001 => populate!(Fahrenheit, 1, dec: -2, ColdWarmTemperatureLimit),
+                    0b111_0010 => populate!(Fahrenheit, 1, dec: -1, ColdWarmTemperatureLimit),
+                    0b111_0011 => populate!(Fahrenheit, 1, dec: 0, ColdWarmTemperatureLimit),
                     0b111_0100 => populate!(Celsius, 1, dec: -3, ColdWarmTemperatureLimit),
                     0b111_0101 => populate!(Celsius, 1, dec: -2, ColdWarmTemperatureLimit),
                     0b111_0110 => populate!(Celsius, 1, dec: -1, ColdWarmTemperatureLimit),
@@ -559,26 +674,25 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
                     0b111_1101 => populate!(Watt, 1, dec: 2, CumaltiveMaximumOfActivePower),
                     0b111_1110 => populate!(Watt, 1, dec: 3, CumaltiveMaximumOfActivePower),
                     0b111_1111 => populate!(Watt, 1, dec: 4, CumaltiveMaximumOfActivePower),
-                    0b110_1000 => populate!(HCAUnit, 1,dec: 0, ResultingRatingFactor),
-                    0b110_1001 => populate!(HCAUnit, 1,dec: 0, ThermalOutputRatingFactor),
-                    0b110_1010 => populate!(HCAUnit, 1,dec: 0, ThermalCouplingRatingFactorOverall),
-                    0b110_1011 => populate!(HCAUnit, 1,dec: 0, ThermalCouplingRatingRoomSide),
-                    0b110_1100 => {
-                        populate!(HCAUnit, 1,dec: 0, ThermalCouplingRatingFactorHeatingSide)
-                    }
-                    0b110_1101 => populate!(HCAUnit, 1,dec: 0, LowTemperatureRatingFactor),
-                    0b110_1110 => populate!(HCAUnit, 1,dec: 0, DisplayOutputScalingFactor),
-
-                    _ => todo!("Implement the rest of the units: {:X?}", first_vife_data),
+                    _ => labels.push(ValueLabel::Reserved),
                 };
             }
             // we need to check if the next byte is equivalent to the length of the rest of the
             // the data. In this case it is very likely that, this is how the payload is built up.
             ValueInformationCoding::PlainText => labels.push(ValueLabel::PlainText),
+            ValueInformationCoding::AnyVIF => labels.push(ValueLabel::AnyVIF),
             ValueInformationCoding::ManufacturerSpecific => {
                 labels.push(ValueLabel::ManufacturerSpecific)
             }
         }
+        /* consume orthogonal vife */
+        consume_orthhogonal_vife(
+            value_information_block,
+            &mut labels,
+            &mut units,
+            &mut decimal_scale_exponent,
+            &mut decimal_offset_exponent,
+        );
 
         Ok(Self {
             decimal_offset_exponent,
@@ -589,6 +703,16 @@ impl TryFrom<&ValueInformationBlock> for ValueInformation {
     }
 }
 
+/// Seconds, minutes, hours or days as selected by the lowest two bits of a time code
+const fn time_unit(data: u8) -> Unit {
+    match data & 0b11 {
+        0 => unit!(Second),
+        1 => unit!(Minute),
+        2 => unit!(Hour),
+        _ => unit!(Day),
+    }
+}
+
 fn consume_orthhogonal_vife(
     value_information_block: &ValueInformationBlock,
     labels: &mut ArrayVec<ValueLabel, 10>,
@@ -596,242 +720,196 @@ fn consume_orthhogonal_vife(
     decimal_scale_exponent: &mut isize,
     decimal_offset_exponent: &mut isize,
 ) {
-    if let Some(vife) = &value_information_block.value_information_extension {
-        let mut is_extension_of_combinable_orthogonal_vife = false;
-        for v in vife {
-            if v.data == 0xFC {
-                is_extension_of_combinable_orthogonal_vife = true;
-                continue;
-            }
-            if is_extension_of_combinable_orthogonal_vife {
-                is_extension_of_combinable_orthogonal_vife = false;
-                match v.data & 0x7F {
-                    0x00 => labels.push(ValueLabel::Reserved),
-                    0x01 => labels.push(ValueLabel::AtPhaseL1),
-                    0x02 => labels.push(ValueLabel::AtPhaseL2),
-                    0x03 => labels.push(ValueLabel::AtPhaseL3),
-                    0x04 => labels.push(ValueLabel::AtNeutral),
-                    0x05 => labels.push(ValueLabel::BetweenPhasesL1L2),
-                    0x06 => labels.push(ValueLabel::BetweenPhasesL2L3),
-                    0x07 => labels.push(ValueLabel::BetweenPhasesL3L1),
-                    0x08 => labels.push(ValueLabel::AtQuadrant1),
-                    0x09 => labels.push(ValueLabel::AtQuadrant2),
-                    0x0A => labels.push(ValueLabel::AtQuadrant3),
-                    0x0B => labels.push(ValueLabel::AtQuadrant4),
-                    0x0C => labels.push(ValueLabel::DeltaBetweenImportAndExport),
-                    0x0F => labels.push(
-                        ValueLabel::AccumulationOfAbsoluteValueBothPositiveAndNegativeContribution,
-                    ),
-                    0x11 => labels.push(ValueLabel::DataPresentedWithTypeC),
-                    0x12 => labels.push(ValueLabel::DataPresentedWithTypeD),
-                    0x13 => labels.push(ValueLabel::DirectionFromCommunicationPartnerToMeter),
-                    0x14 => labels.push(ValueLabel::DirectionFromMeterToCommunicationPartner),
-                    _ => labels.push(ValueLabel::Reserved),
-                }
-            } else {
-                match v.data & 0x7F {
-                    0x00..=0x0F => labels.push(ValueLabel::ReservedForObjectActions),
-                    0x10..=0x11 => labels.push(ValueLabel::Reserved),
-                    0x12 => labels.push(ValueLabel::Averaged),
-                    0x13 => labels.push(ValueLabel::InverseCompactProfile),
-                    0x14 => labels.push(ValueLabel::RelativeDeviation),
-                    0x15..=0x1C => labels.push(ValueLabel::RecoordErrorCodes),
-                    0x1D => labels.push(ValueLabel::StandardConformDataContent),
-                    0x1E => labels.push(ValueLabel::CompactProfileWithRegisterNumbers),
-                    0x1F => labels.push(ValueLabel::CompactProfile),
-                    0x20 => units.push(unit!(Second ^ -1)),
-                    0x21 => units.push(unit!(Minute ^ -1)),
-                    0x22 => units.push(unit!(Hour ^ -1)),
-                    0x23 => units.push(unit!(Day ^ -1)),
-                    0x24 => units.push(unit!(Week ^ -1)),
-                    0x25 => units.push(unit!(Month ^ -1)),
-                    0x26 => units.push(unit!(Year ^ -1)),
-                    0x27 => units.push(unit!(Revolution ^ -1)),
-                    0x28 => {
-                        units.push(unit!(Increment));
-                        units.push(unit!(InputPulseOnChannel0 ^ -1));
-                    }
-                    0x29 => {
-                        units.push(unit!(Increment));
-                        units.push(unit!(OutputPulseOnChannel0 ^ -1));
-                    }
-                    0x2A => {
-                        units.push(unit!(Increment));
-                        units.push(unit!(InputPulseOnChannel1 ^ -1));
-                    }
-                    0x2B => {
-                        units.push(unit!(Increment));
-                        units.push(unit!(OutputPulseOnChannel1 ^ -1));
-                    }
-                    0x2C => units.push(unit!(Liter)),
-                    0x2D => units.push(unit!(Meter ^ -3)),
-                    0x2E => units.push(unit!(Kilogram ^ -1)),
-                    0x2F => units.push(unit!(Kelvin ^ -1)),
-                    0x30 => {
-                        units.push(unit!(Watt ^ -1));
-                        units.push(unit!(Hour ^ -1));
-                        *decimal_scale_exponent -= 3;
-                    }
-                    0x31 => {
-                        units.push(unit!(Joul ^ -1));
-                        *decimal_scale_exponent += -9;
-                    }
-                    0x32 => {
-                        units.push(unit!(Watt ^ -1));
-                        *decimal_scale_exponent += -3;
-                    }
-                    0x33 => {
-                        units.push(unit!(Kelvin ^ -1));
-                        units.push(unit!(Liter ^ -1));
-                    }
-                    0x34 => units.push(unit!(Volt ^ -1)),
-                    0x35 => units.push(unit!(Ampere ^ -1)),
-                    0x36 => units.push(unit!(Second ^ 1)),
-                    0x37 => {
-                        units.push(unit!(Second ^ 1));
-                        units.push(unit!(Volt ^ -1));
-                    }
-                    0x38 => {
-                        units.push(unit!(Second ^ 1));
-                        units.push(unit!(Ampere ^ -1));
-                    }
-                    0x39 => labels.push(ValueLabel::StartDateOf),
-                    0x3A => labels.push(ValueLabel::VifContinsUncorrectedUnitOrValue),
-                    0x3B => labels.push(ValueLabel::AccumulationOnlyIfValueIsPositive),
-                    0x3C => labels.push(ValueLabel::AccumulationOnlyIfValueIsNegative),
-                    0x3D => labels.push(ValueLabel::NoneMetricUnits),
-                    0x3E => labels.push(ValueLabel::ValueAtBaseConditions),
-                    0x3F => labels.push(ValueLabel::ObisDecleration),
-                    0x40 => labels.push(ValueLabel::UpperLimitValue),
-                    0x48 => labels.push(ValueLabel::LowerLimitValue),
-                    0x41 => labels.push(ValueLabel::NumberOfExceedsOfUpperLimitValue),
-                    0x49 => labels.push(ValueLabel::NumberOfExceedsOfLowerLimitValue),
-                    0x42 => labels.push(ValueLabel::DateOfBeginFirstLowerLimitExceed),
-                    0x43 => labels.push(ValueLabel::DateOfBeginFirstUpperLimitExceed),
-                    0x46 => labels.push(ValueLabel::DateOfBeginLastLowerLimitExceed),
-                    0x47 => labels.push(ValueLabel::DateOfBeginLastUpperLimitExceed),
-                    0x4A => labels.push(ValueLabel::DateOfEndLastLowerLimitExceed),
-                    0x4B => labels.push(ValueLabel::DateOfEndLastUpperLimitExceed),
-                    0x4E => labels.push(ValueLabel::DateOfEndFirstLowerLimitExceed),
-                    0x4F => labels.push(ValueLabel::DateOfEndFirstUpperLimitExceed),
-                    0x50 => {
-                        labels.push(ValueLabel::DurationOfFirstLowerLimitExceed);
-                        units.push(unit!(Second));
-                    }
-                    0x51 => {
-                        labels.push(ValueLabel::DurationOfFirstLowerLimitExceed);
-                        units.push(unit!(Minute));
-                    }
-                    0x52 => {
-                        labels.push(ValueLabel::DurationOfFirstLowerLimitExceed);
-                        units.push(unit!(Hour));
-                    }
-                    0x53 => {
-                        labels.push(ValueLabel::DurationOfFirstLowerLimitExceed);
-                        units.push(unit!(Day));
-                    }
-                    0x54 => {
-                        labels.push(ValueLabel::DurationOfFirstUpperLimitExceed);
-                        units.push(unit!(Second));
-                    }
-                    0x55 => {
-                        labels.push(ValueLabel::DurationOfFirstUpperLimitExceed);
-                        units.push(unit!(Minute));
-                    }
-                    0x56 => {
-                        labels.push(ValueLabel::DurationOfFirstUpperLimitExceed);
-                        units.push(unit!(Hour));
-                    }
-                    0x57 => {
-                        labels.push(ValueLabel::DurationOfFirstUpperLimitExceed);
-                        units.push(unit!(Day));
-                    }
-                    0x58 => {
-                        labels.push(ValueLabel::DurationOfLastLowerLimitExceed);
-                        units.push(unit!(Second));
-                    }
-                    0x59 => {
-                        labels.push(ValueLabel::DurationOfLastLowerLimitExceed);
-                        units.push(unit!(Minute));
-                    }
-                    0x5A => {
-                        labels.push(ValueLabel::DurationOfLastLowerLimitExceed);
-                        units.push(unit!(Hour));
-                    }
-                    0x5B => {
-                        labels.push(ValueLabel::DurationOfLastLowerLimitExceed);
-                        units.push(unit!(Day));
-                    }
-                    0x5C => {
-                        labels.push(ValueLabel::DurationOfLastUpperLimitExceed);
-                        units.push(unit!(Second));
-                    }
-                    0x5D => {
-                        labels.push(ValueLabel::DurationOfLastUpperLimitExceed);
-                        units.push(unit!(Minute));
-                    }
-                    0x5E => {
-                        labels.push(ValueLabel::DurationOfLastUpperLimitExceed);
-                        units.push(unit!(Hour));
-                    }
-                    0x5F => {
-                        labels.push(ValueLabel::DurationOfLastUpperLimitExceed);
-                        units.push(unit!(Day));
-                    }
-                    0x60 => {
-                        labels.push(ValueLabel::DurationOfFirst);
-                        units.push(unit!(Second));
-                    }
-                    0x61 => {
-                        labels.push(ValueLabel::DurationOfFirst);
-                        units.push(unit!(Minute));
-                    }
-                    0x62 => {
-                        labels.push(ValueLabel::DurationOfFirst);
-                        units.push(unit!(Hour));
-                    }
-                    0x63 => {
-                        labels.push(ValueLabel::DurationOfFirst);
-                        units.push(unit!(Day));
-                    }
-                    0x64 => {
-                        labels.push(ValueLabel::DurationOfLast);
-                        units.push(unit!(Second));
-                    }
-                    0x65 => {
-                        labels.push(ValueLabel::DurationOfLast);
-                        units.push(unit!(Minute));
-                    }
-                    0x66 => {
-                        labels.push(ValueLabel::DurationOfLast);
-                        units.push(unit!(Day));
-                    }
-                    0x68 => labels.push(ValueLabel::ValueDuringLowerValueExeed),
-                    0x6C => labels.push(ValueLabel::ValueDuringUpperValueExceed),
-                    0x69 => labels.push(ValueLabel::LeakageValues),
-                    0x6D => labels.push(ValueLabel::OverflowValues),
-                    0x6A => labels.push(ValueLabel::DateOfBeginFirst),
-                    0x6B => labels.push(ValueLabel::DateOfBeginLast),
-                    0x6E => labels.push(ValueLabel::DateOfEndLast),
-                    0x6F => labels.push(ValueLabel::DateOfEndFirst),
-                    0x70..=0x77 => {
-                        *decimal_scale_exponent += (v.data & 0b111) as isize - 6;
-                    }
-                    0x78..=0x7B => {
-                        *decimal_offset_exponent += (v.data & 0b11) as isize - 3;
-                    }
-                    0x7D => labels.push(ValueLabel::MultiplicativeCorrectionFactor103),
-                    0x7E => labels.push(ValueLabel::FutureValue),
-                    0x7F => {
-                        labels.push(ValueLabel::NextVIFEAndDataOfThisBlockAreManufacturerSpecific)
-                    }
-                    _ => labels.push(ValueLabel::Reserved),
-                };
+    let Some(vife) = &value_information_block.value_information_extension else {
+        return;
+    };
+    for v in vife {
+        // every code adds at most one label and two units
+        if labels.is_full() || units.remaining_capacity() < 2 {
+            break;
+        }
+        match v.coding {
+            ValueInformationFieldExtensionCoding::ComninableOrthogonalVIFECodeExtension => {
+                consume_combinable_orthogonal_vife(
+                    v.data & 0x7F,
+                    labels,
+                    units,
+                    decimal_scale_exponent,
+                    decimal_offset_exponent,
+                )
             }
+            ValueInformationFieldExtensionCoding::ExtensionOfCombinableOrthogonalVIFECode => labels
+                .push(match v.data & 0x7F {
+                    0x01 => ValueLabel::AtPhaseL1,
+                    0x02 => ValueLabel::AtPhaseL2,
+                    0x03 => ValueLabel::AtPhaseL3,
+                    0x04 => ValueLabel::AtNeutral,
+                    0x05 => ValueLabel::BetweenPhasesL1L2,
+                    0x06 => ValueLabel::BetweenPhasesL2L3,
+                    0x07 => ValueLabel::BetweenPhasesL3L1,
+                    0x08 => ValueLabel::AtQuadrant1,
+                    0x09 => ValueLabel::AtQuadrant2,
+                    0x0A => ValueLabel::AtQuadrant3,
+                    0x0B => ValueLabel::AtQuadrant4,
+                    0x0C => ValueLabel::DeltaBetweenImportAndExport,
+                    0x10 => {
+                        ValueLabel::AccumulationOfAbsoluteValueBothPositiveAndNegativeContribution
+                    }
+                    0x11 => ValueLabel::DataPresentedWithTypeC,
+                    0x12 => ValueLabel::DataPresentedWithTypeD,
+                    0x13 => ValueLabel::DirectionFromCommunicationPartnerToMeter,
+                    0x14 => ValueLabel::DirectionFromMeterToCommunicationPartner,
+                    _ => ValueLabel::Reserved,
+                }),
+            // codes of the VIF extension tables and manufacturer specific codes are no qualifiers
+            ValueInformationFieldExtensionCoding::MainVIFCodeExtension
+            | ValueInformationFieldExtensionCoding::AlternateVIFCodeExtension
+            | ValueInformationFieldExtensionCoding::ReservedAlternateVIFCodeExtension
+            | ValueInformationFieldExtensionCoding::ManufacturerSpecificVIFECode => {}
         }
     }
 }
 
+fn consume_combinable_orthogonal_vife(
+    code: u8,
+    labels: &mut ArrayVec<ValueLabel, 10>,
+    units: &mut ArrayVec<Unit, 10>,
+    decimal_scale_exponent: &mut isize,
+    decimal_offset_exponent: &mut isize,
+) {
+    match code {
+        0x00..=0x0F => labels.push(ValueLabel::ReservedForObjectActions),
+        0x10..=0x11 => labels.push(ValueLabel::Reserved),
+        0x12 => labels.push(ValueLabel::Averaged),
+        0x13 => labels.push(ValueLabel::InverseCompactProfile),
+        0x14 => labels.push(ValueLabel::RelativeDeviation),
+        0x15..=0x1C => labels.push(ValueLabel::RecoordErrorCodes),
+        0x1D => labels.push(ValueLabel::StandardConformDataContent),
+        0x1E => labels.push(ValueLabel::CompactProfileWithRegisterNumbers),
+        0x1F => labels.push(ValueLabel::CompactProfile),
+        0x20 => units.push(unit!(Second ^ -1)),
+        0x21 => units.push(unit!(Minute ^ -1)),
+        0x22 => units.push(unit!(Hour ^ -1)),
+        0x23 => units.push(unit!(Day ^ -1)),
+        0x24 => units.push(unit!(Week ^ -1)),
+        0x25 => units.push(unit!(Month ^ -1)),
+        0x26 => units.push(unit!(Year ^ -1)),
+        0x27 => units.push(unit!(Revolution ^ -1)),
+        0x28 => {
+            units.push(unit!(Increment));
+            units.push(unit!(InputPulseOnChannel0 ^ -1));
+        }
+        0x29 => {
+            units.push(unit!(Increment));
+            units.push(unit!(OutputPulseOnChannel0 ^ -1));
+        }
+        0x2A => {
+            units.push(unit!(Increment));
+            units.push(unit!(InputPulseOnChannel1 ^ -1));
+        }
+        0x2B => {
+            units.push(unit!(Increment));
+            units.push(unit!(OutputPulseOnChannel1 ^ -1));
+        }
+        0x2C => units.push(unit!(Liter ^ -1)),
+        0x2D => units.push(unit!(Meter ^ -3)),
+        0x2E => units.push(unit!(Kilogram ^ -1)),
+        0x2F => units.push(unit!(Kelvin ^ -1)),
+        0x30 => {
+            units.push(unit!(Watt ^ -1));
+            units.push(unit!(Hour ^ -1));
+            *decimal_scale_exponent -= 3;
+        }
+        0x31 => {
+            units.push(unit!(Joul ^ -1));
+            *decimal_scale_exponent += -9;
+        }
+        0x32 => {
+            units.push(unit!(Watt ^ -1));
+            *decimal_scale_exponent += -3;
+        }
+        0x33 => {
+            units.push(unit!(Kelvin ^ -1));
+            units.push(unit!(Liter ^ -1));
+        }
+        0x34 => units.push(unit!(Volt ^ -1)),
+        0x35 => units.push(unit!(Ampere ^ -1)),
+        0x36 => units.push(unit!(Second ^ 1)),
+        0x37 => {
+            units.push(unit!(Second ^ 1));
+            units.push(unit!(Volt ^ -1));
+        }
+        0x38 => {
+            units.push(unit!(Second ^ 1));
+            units.push(unit!(Ampere ^ -1));
+        }
+        0x39 => labels.push(ValueLabel::StartDateOf),
+        0x3A => labels.push(ValueLabel::VifContinsUncorrectedUnitOrValue),
+        0x3B => labels.push(ValueLabel::AccumulationOnlyIfValueIsPositive),
+        0x3C => labels.push(ValueLabel::AccumulationOnlyIfValueIsNegative),
+        0x3D => labels.push(ValueLabel::NoneMetricUnits),
+        0x3E => labels.push(ValueLabel::ValueAtBaseConditions),
+        0x3F => labels.push(ValueLabel::ObisDecleration),
+        // E100 u000 limit value, E100 u001 number of exceeds, u = lower or upper
+        0x40 => labels.push(ValueLabel::LowerLimitValue),
+        0x48 => labels.push(ValueLabel::UpperLimitValue),
+        0x41 => labels.push(ValueLabel::NumberOfExceedsOfLowerLimitValue),
+        0x49 => labels.push(ValueLabel::NumberOfExceedsOfUpperLimitValue),
+        // E100 uf1b date of the limit exceed, f = first or last, b = begin or end
+        0x42 => labels.push(ValueLabel::DateOfBeginFirstLowerLimitExceed),
+        0x43 => labels.push(ValueLabel::DateOfEndFirstLowerLimitExceed),
+        0x46 => labels.push(ValueLabel::DateOfBeginLastLowerLimitExceed),
+        0x47 => labels.push(ValueLabel::DateOfEndLastLowerLimitExceed),
+        0x4A => labels.push(ValueLabel::DateOfBeginFirstUpperLimitExceed),
+        0x4B => labels.push(ValueLabel::DateOfEndFirstUpperLimitExceed),
+        0x4E => labels.push(ValueLabel::DateOfBeginLastUpperLimitExceed),
+        0x4F => labels.push(ValueLabel::DateOfEndLastUpperLimitExceed),
+        // E101 ufnn duration of the limit exceed, nn = seconds to days
+        0x50..=0x5F => {
+            labels.push(match code & 0b1100 {
+                0b0000 => ValueLabel::DurationOfFirstLowerLimitExceed,
+                0b0100 => ValueLabel::DurationOfLastLowerLimitExceed,
+                0b1000 => ValueLabel::DurationOfFirstUpperLimitExceed,
+                _ => ValueLabel::DurationOfLastUpperLimitExceed,
+            });
+            units.push(time_unit(code));
+        }
+        // E110 0fnn duration of the first or last d
+        0x60..=0x63 => {
+            labels.push(ValueLabel::DurationOfFirst);
+            units.push(time_unit(code));
+        }
+        0x64..=0x67 => {
+            labels.push(ValueLabel::DurationOfLast);
+            units.push(time_unit(code));
+        }
+        0x68 => labels.push(ValueLabel::ValueDuringLowerValueExeed),
+        0x6C => labels.push(ValueLabel::ValueDuringUpperValueExceed),
+        0x69 => labels.push(ValueLabel::LeakageValues),
+        0x6D => labels.push(ValueLabel::OverflowValues),
+        // E110 1f1b date of the begin or end of the first or last d
+        0x6A => labels.push(ValueLabel::DateOfBeginFirst),
+        0x6B => labels.push(ValueLabel::DateOfEndFirst),
+        0x6E => labels.push(ValueLabel::DateOfBeginLast),
+        0x6F => labels.push(ValueLabel::DateOfEndLast),
+        0x70..=0x77 => {
+            *decimal_scale_exponent += (code & 0b111) as isize - 6;
+        }
+        0x78..=0x7B => {
+            *decimal_offset_exponent += (code & 0b11) as isize - 3;
+        }
+        // the next VIFE is a code of the second level table
+        0x7C => {}
+        0x7D => {
+            labels.push(ValueLabel::MultiplicativeCorrectionFactor103);
+            *decimal_scale_exponent += 3;
+        }
+        0x7E => labels.push(ValueLabel::FutureValue),
+        0x7F => labels.push(ValueLabel::NextVIFEAndDataOfThisBlockAreManufacturerSpecific),
+        _ => labels.push(ValueLabel::Reserved),
+    }
+}
+
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ValueInformationError {
@@ -1041,6 +1119,21 @@ pub enum ValueLabel {
     DisplayOutputScalingFactor,
     ManufacturerSpecific,
     Volume,
+    FlowTemperature,
+    ReturnTemperature,
+    OnTime,
+    OperatingTime,
+    TemperatureDifference,
+    ExternalTemperature,
+    Pressure,
+    AnyVIF,
+    OperatorSpecificData,
+    TimePointSecond,
+    DurationSinceLastReadout,
+    StartOfTariff,
+    DurationOfTariff,
+    PeriodOfTariff,
+    Dimensionless,
 }
 
 #[cfg(feature = "std")]
@@ -1132,6 +1225,9 @@ pub enum UnitName {
     Degree,
     Hertz,
     HCAUnit,
+    Fahrenheit,
+    Calorie,
+    AmericanGallon,
 }
 
 #[cfg(feature = "std")]
@@ -1166,7 +1262,7 @@ impl fmt::Display for UnitName {
             UnitName::InputPulseOnChannel1 => write!(f, "InputPulseOnChannel1"),
             UnitName::OutputPulseOnChannel1 => write!(f, "OutputPulseOnChannel1"),
             UnitName::Liter => write!(f, "l"),
-            UnitName::Volt => write!(f, "A"),
+            UnitName::Volt => write!(f, "V"),
             UnitName::Ampere => write!(f, "A"),
             UnitName::LocalMoneyCurrency => write!(f, "$ (local)"),
             UnitName::Symbol => write!(f, "Symbol"),
@@ -1176,6 +1272,9 @@ impl fmt::Display for UnitName {
             UnitName::Degree => write!(f, "°"),
             UnitName::Hertz => write!(f, "Hz"),
             UnitName::HCAUnit => write!(f, "HCAUnit"),
+            UnitName::Fahrenheit => write!(f, "°F"),
+            UnitName::Calorie => write!(f, "cal"),
+            UnitName::AmericanGallon => write!(f, "gal (US)"),
         }
     }
 }
@@ -1405,7 +1504,7 @@ mod tests {
             ValueInformation::try_from(&result).unwrap(),
             ValueInformation {
                 decimal_offset_exponent: 0,
-                decimal_scale_exponent: 0,
+                decimal_scale_exponent: -2,
                 units: { ArrayVec::<Unit, 10>::new() },
                 labels: {
                     let mut x = ArrayVec::<ValueLabel, 10>::new();
@@ -1433,4 +1532,216 @@ mod tests {
         let result = ValueInformationBlock::try_from(data.as_slice()).unwrap();
         assert_eq!(result.get_size(), 2);
     }
+
+    #[test]
+    fn test_extension_tables() {
+        use crate::user_data::value_information::UnitName;
+        use crate::user_data::value_information::{
+            Unit, ValueInformation, ValueInformationBlock, ValueLabel,
+        };
+
+        let parse = |data: &[u8]| {
+            ValueInformation::try_from(&ValueInformationBlock::try_from(data).unwrap()).unwrap()
+        };
+
+        /* VIB = 0xFB, 0x00 => energy in 0.1 MWh */
+        let value_information = parse(&[0xFB, 0x00]);
+        assert_eq!(
+            value_information.units.as_slice(),
+            &[unit!(Watt), unit!(Hour)]
+        );
+        assert_eq!(value_information.decimal_scale_exponent, 5);
+        assert_eq!(value_information.labels.as_slice(), &[ValueLabel::Energy]);
+
+        /* VIB = 0xFB, 0x14 => reactive power in 0.001 kVAR, 0xFB, 0x34 => apparent power in
+        0.001 kVA */
+        let value_information = parse(&[0xFB, 0x14]);
+        assert_eq!(value_information.units.as_slice(), &[unit!(ReactiveWatt)]);
+        assert_eq!(value_information.decimal_scale_exponent, 0);
+        let value_information = parse(&[0xFB, 0x34]);
+        assert_eq!(value_information.units.as_slice(), &[unit!(ApparentWatt)]);
+        assert_eq!(value_information.decimal_scale_exponent, 0);
+        /* VIB = 0xFB, 0x37 => apparent power in kVA */
+        assert_eq!(parse(&[0xFB, 0x37]).decimal_scale_exponent, 3);
+
+        /* VIB = 0xFB, 0x5A => flow temperature in 0.1 °F */
+        let value_information = parse(&[0xFB, 0x5A]);
+        assert_eq!(value_information.units.as_slice(), &[unit!(Fahrenheit)]);
+        assert_eq!(value_information.decimal_scale_exponent, -1);
+        assert_eq!(
+            value_information.labels.as_slice(),
+            &[ValueLabel::FlowTemperature]
+        );
+
+        /* VIB = 0xFB, 0x24 => volume flow in 0.001 american gallon per minute */
+        let value_information = parse(&[0xFB, 0x24]);
+        let units: [Unit; 2] = [unit!(AmericanGallon), unit!(Minute ^ -1)];
+        assert_eq!(value_information.units.as_slice(), &units);
+        assert_eq!(value_information.decimal_scale_exponent, -3);
+
+        /* VIB = 0xFD, 0x48 => 0.1 V, 0xFD, 0x5C => 1 A */
+        let value_information = parse(&[0xFD, 0x48]);
+        assert_eq!(value_information.units.as_slice(), &[unit!(Volt)]);
+        assert_eq!(value_information.decimal_scale_exponent, -1);
+        let value_information = parse(&[0xFD, 0x5C]);
+        assert_eq!(value_information.units.as_slice(), &[unit!(Ampere)]);
+        assert_eq!(value_information.decimal_scale_exponent, 0);
+
+        /* VIB = 0xFD, 0x3D => period of nominal data transmissions in minutes */
+        let value_information = parse(&[0xFD, 0x3D]);
+        assert_eq!(value_information.units.as_slice(), &[unit!(Minute)]);
+        assert_eq!(
+            value_information.labels.as_slice(),
+            &[ValueLabel::PeriodOfNormalDataTransmition]
+        );
+
+        /* VIB = 0x21 => on time in minutes */
+        let value_information = parse(&[0x21]);
+        assert_eq!(value_information.units.as_slice(), &[unit!(Minute)]);
+        assert_eq!(value_information.labels.as_slice(), &[ValueLabel::OnTime]);
+
+        /* reserved codes */
+        for data in [[0x6F].as_slice(), &[0xFB, 0x40], &[0xFD, 0x77]] {
+            assert_eq!(parse(data).labels.as_slice(), &[ValueLabel::Reserved]);
+        }
+        assert_eq!(parse(&[0x7E]).labels.as_slice(), &[ValueLabel::AnyVIF]);
+    }
+
+    #[test]
+    fn test_all_value_information_codes() {
+        use crate::user_data::data_information::DataInformationError;
+        use crate::user_data::value_information::{ValueInformation, ValueInformationBlock};
+
+        /* no code of the primary or the extension tables may abort the parser */
+        for vif in 0..=u8::MAX {
+            for vife in 0..=0x7F {
+                let data = [vif, vife, 0x00];
+                if let Ok(block) = ValueInformationBlock::try_from(data.as_slice()) {
+                    let _ = ValueInformation::try_from(&block);
+                }
+            }
+        }
+
+        /* the extension VIFs without extension bit lack their code */
+        let block = ValueInformationBlock::try_from([0x7D].as_slice()).unwrap();
+        assert_eq!(
+            ValueInformation::try_from(&block),
+            Err(DataInformationError::InvalidValueInformation)
+        );
+    }
+
+    #[test]
+    fn test_combinable_orthogonal_vife() {
+        use crate::user_data::data_information::DataInformationError;
+        use crate::user_data::value_information::UnitName;
+        use crate::user_data::value_information::{
+            Unit, ValueInformation, ValueInformationBlock, ValueInformationFieldExtensionCoding,
+            ValueLabel,
+        };
+
+        let parse = |data: &[u8]| {
+            ValueInformation::try_from(&ValueInformationBlock::try_from(data).unwrap()).unwrap()
+        };
+        let codings = |data: &[u8]| {
+            ValueInformationBlock::try_from(data)
+                .unwrap()
+                .value_information_extension
+                .unwrap()
+                .iter()
+                .map(|vife| vife.coding.clone())
+                .collect::<arrayvec::ArrayVec<_, 10>>()
+        };
+
+        /* VIB = 0x83, 0xFC, 0x81, 0x3B => energy at phase L1, only positive contributions */
+        let data = [0x83, 0xFC, 0x81, 0x3B];
+        assert_eq!(
+            codings(&data).as_slice(),
+            &[
+                ValueInformationFieldExtensionCoding::ComninableOrthogonalVIFECodeExtension,
+                ValueInformationFieldExtensionCoding::ExtensionOfCombinableOrthogonalVIFECode,
+                ValueInformationFieldExtensionCoding::ComninableOrthogonalVIFECodeExtension,
+            ]
+        );
+        assert_eq!(
+            parse(&data).labels.as_slice(),
+            &[
+                ValueLabel::AtPhaseL1,
+                ValueLabel::AccumulationOnlyIfValueIsPositive
+            ]
+        );
+        assert_eq!(
+            parse(&[0x83, 0xFC, 0x0A]).labels.as_slice(),
+            &[ValueLabel::AtQuadrant3]
+        );
+
+        /* VIB = 0x83, 0xFF, 0x05 => the VIFE 0x05 is manufacturer specific */
+        let data = [0x83, 0xFF, 0x05];
+        assert_eq!(
+            codings(&data).as_slice(),
+            &[
+                ValueInformationFieldExtensionCoding::ComninableOrthogonalVIFECodeExtension,
+                ValueInformationFieldExtensionCoding::ManufacturerSpecificVIFECode,
+            ]
+        );
+        assert_eq!(
+            parse(&data).labels.as_slice(),
+            &[ValueLabel::NextVIFEAndDataOfThisBlockAreManufacturerSpecific]
+        );
+
+        /* VIB = 0x83, 0x7D => energy in Wh multiplied by 10^3 */
+        let value_information = parse(&[0x83, 0x7D]);
+        assert_eq!(value_information.decimal_scale_exponent, 3);
+        assert_eq!(
+            value_information.labels.as_slice(),
+            &[ValueLabel::MultiplicativeCorrectionFactor103]
+        );
+
+        /* VIB = 0xFD, 0xC8, 0x48 => upper limit of the voltage */
+        let data = [0xFD, 0xC8, 0x48];
+        assert_eq!(
+            codings(&data).as_slice(),
+            &[
+                ValueInformationFieldExtensionCoding::MainVIFCodeExtension,
+                ValueInformationFieldExtensionCoding::ComninableOrthogonalVIFECodeExtension,
+            ]
+        );
+        let value_information = parse(&data);
+        assert_eq!(value_information.units.as_slice(), &[unit!(Volt)]);
+        assert_eq!(
+            value_information.labels.as_slice(),
+            &[ValueLabel::UpperLimitValue]
+        );
+
+        /* limit exceeds */
+        assert_eq!(
+            parse(&[0x93, 0x4B]).labels.as_slice(),
+            &[
+                ValueLabel::Volume,
+                ValueLabel::DateOfEndFirstUpperLimitExceed
+            ]
+        );
+        let value_information = parse(&[0x93, 0x5A]);
+        assert_eq!(
+            value_information.labels.as_slice(),
+            &[
+                ValueLabel::Volume,
+                ValueLabel::DurationOfFirstUpperLimitExceed
+            ]
+        );
+        let units: [Unit; 2] = [unit!(Meter ^ 3), unit!(Hour)];
+        assert_eq!(value_information.units.as_slice(), &units);
+
+        /* more qualifiers than fit into the value information are dropped */
+        let data = [
+            0x93, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0x30,
+        ];
+        assert_eq!(parse(&data).units.len(), 9);
+        let data = [
+            0x93, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0xB0, 0x30,
+        ];
+        assert_eq!(
+            ValueInformationBlock::try_from(data.as_slice()),
+            Err(DataInformationError::InvalidValueInformation)
+        );
+    }
 }
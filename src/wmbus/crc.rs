@@ -0,0 +1,34 @@
+const POLYNOMIAL: u16 = 0x3D65;
+
+/// CRC-16 of EN 13757-4, the result is sent most significant byte first
+#[must_use]
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ POLYNOMIAL
+            } else {
+                crc << 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0xC2B7);
+        assert_eq!(crc16(&[]), 0xFFFF);
+        // first block of the example telegram of security profile A in OMS Vol. 2 Annex N
+        assert_eq!(
+            crc16(&[0x2E, 0x44, 0x93, 0x15, 0x78, 0x56, 0x34, 0x12, 0x33, 0x03]),
+            0x3363
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Link layer fields of wireless M-Bus telegrams (EN 13757-4)
+use super::{crc16, WirelessFrameError};
+use crate::frames::Function;
+use crate::user_data::SecondaryAddress;
+
+/// C field of a wireless telegram, the functions shared with the wired link layer are kept
+/// as [`Function`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WirelessFunction {
+    /// Acknowledge of a received telegram
+    Ack,
+    /// Negative acknowledge, the received telegram is rejected
+    Nack,
+    /// Send, no reply, e.g. periodic meter data
+    SndNr,
+    /// Send installation request
+    SndIr,
+    /// Access, no reply, the meter offers a short reception window
+    AccNr,
+    /// Access demand, the meter requests an access by the other station
+    AccDmd,
+    /// Confirm installation request
+    CnfIr,
+    Wired(Function),
+}
+
+impl TryFrom<u8> for WirelessFunction {
+    type Error = WirelessFrameError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0x00 => Ok(Self::Ack),
+            0x01 => Ok(Self::Nack),
+            0x44 => Ok(Self::SndNr),
+            0x46 => Ok(Self::SndIr),
+            0x47 => Ok(Self::AccNr),
+            0x48 => Ok(Self::AccDmd),
+            0x06 => Ok(Self::CnfIr),
+            _ => Function::try_from(byte)
+                .map(Self::Wired)
+                .map_err(|_| WirelessFrameError::InvalidFunction { byte }),
+        }
+    }
+}
+
+impl From<&WirelessFunction> for u8 {
+    fn from(function: &WirelessFunction) -> Self {
+        match function {
+            WirelessFunction::Ack => 0x00,
+            WirelessFunction::Nack => 0x01,
+            WirelessFunction::SndNr => 0x44,
+            WirelessFunction::SndIr => 0x46,
+            WirelessFunction::AccNr => 0x47,
+            WirelessFunction::AccDmd => 0x48,
+            WirelessFunction::CnfIr => 0x06,
+            WirelessFunction::Wired(function) => u8::from(function),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for WirelessFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WirelessFunction::Ack => write!(f, "Ack"),
+            WirelessFunction::Nack => write!(f, "Nack"),
+            WirelessFunction::SndNr => write!(f, "SndNr"),
+            WirelessFunction::SndIr => write!(f, "SndIr"),
+            WirelessFunction::AccNr => write!(f, "AccNr"),
+            WirelessFunction::AccDmd => write!(f, "AccDmd"),
+            WirelessFunction::CnfIr => write!(f, "CnfIr"),
+            WirelessFunction::Wired(function) => write!(f, "{}", function),
+        }
+    }
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct CommunicationControl: u8 {
+        const REPEATED_ACCESS   = 0b0000_0010;
+        const ACCESSIBILITY     = 0b0000_0100;
+        const PRIORITY          = 0b0000_1000;
+        const HOP_COUNT         = 0b0001_0000;
+        const SYNCHRONIZED      = 0b0010_0000;
+        const RESPONSE_DELAY    = 0b0100_0000;
+        const BIDIRECTIONAL     = 0b1000_0000;
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CommunicationControl {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "CommunicationControl({=u8:#04x})", self.bits());
+    }
+}
+
+/// Session number of the extended link layer
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SessionNumber {
+    /// Encryption mode of the payload following the session number, 0 is unencrypted
+    pub encryption: u8,
+    /// Minutes since the start of the session
+    pub time: u32,
+    pub session: u8,
+}
+
+impl From<u32> for SessionNumber {
+    fn from(value: u32) -> Self {
+        Self {
+            encryption: (value >> 29) as u8,
+            time: (value >> 4) & 0x01FF_FFFF,
+            session: (value & 0x0F) as u8,
+        }
+    }
+}
+
+/// Extended link layer (CI 0x8C to 0x8F), placed between the link layer and the transport
+/// layer
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ExtendedLinkLayer {
+    pub communication_control: CommunicationControl,
+    pub access_number: u8,
+    /// Second address (M2 and A2 field), e.g. the meter behind a repeater or the receiver
+    /// of a command
+    pub address: Option<SecondaryAddress>,
+    pub session_number: Option<SessionNumber>,
+    /// CRC over the payload following the extended link layer
+    pub payload_crc: Option<u16>,
+}
+
+impl ExtendedLinkLayer {
+    /// Length of the extended link layer without the CI field, `None` for other CI fields
+    #[must_use]
+    pub const fn length(ci: u8) -> Option<usize> {
+        match ci {
+            0x8C => Some(2),
+            0x8D => Some(8),
+            0x8E => Some(10),
+            0x8F => Some(16),
+            _ => None,
+        }
+    }
+
+    /// Parses the extended link layer at the start of `data`, returns it together with the
+    /// remaining data starting with the CI field of the transport layer. Data without an
+    /// extended link layer is returned unchanged.
+    pub fn parse(data: &[u8]) -> Result<(Option<Self>, &[u8]), WirelessFrameError> {
+        let ci = *data.first().ok_or(WirelessFrameError::LengthShort)?;
+        let Some(length) = Self::length(ci) else {
+            return Ok((None, data));
+        };
+        let mut iter = data
+            .get(1..=length)
+            .ok_or(WirelessFrameError::LengthShort)?
+            .iter()
+            .copied();
+        let mut next = || iter.next().ok_or(WirelessFrameError::LengthShort);
+
+        let communication_control = CommunicationControl::from_bits_retain(next()?);
+        let access_number = next()?;
+        let address = if ci == 0x8E || ci == 0x8F {
+            let (m1, m2) = (next()?, next()?);
+            Some(SecondaryAddress::from_bytes([
+                next()?,
+                next()?,
+                next()?,
+                next()?,
+                m1,
+                m2,
+                next()?,
+                next()?,
+            ]))
+        } else {
+            None
+        };
+        let (session_number, payload_crc) = if ci == 0x8D || ci == 0x8F {
+            let session_number = u32::from_le_bytes([next()?, next()?, next()?, next()?]);
+            let payload_crc = u16::from_le_bytes([next()?, next()?]);
+            (Some(SessionNumber::from(session_number)), Some(payload_crc))
+        } else {
+            (None, None)
+        };
+
+        Ok((
+            Some(Self {
+                communication_control,
+                access_number,
+                address,
+                session_number,
+                payload_crc,
+            }),
+            data.get(1 + length..)
+                .ok_or(WirelessFrameError::LengthShort)?,
+        ))
+    }
+
+    /// Checks the payload CRC, only meaningful for unencrypted payloads. Returns `true` if
+    /// the extended link layer has no payload CRC.
+    #[must_use]
+    pub fn verify_payload_crc(&self, payload: &[u8]) -> bool {
+        self.payload_crc.is_none_or(|crc| crc == crc16(payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wireless_function() {
+        assert_eq!(
+            WirelessFunction::try_from(0x44),
+            Ok(WirelessFunction::SndNr)
+        );
+        assert_eq!(
+            WirelessFunction::try_from(0x08),
+            Ok(WirelessFunction::Wired(Function::RspUd {
+                acd: false,
+                dfc: false
+            }))
+        );
+        assert_eq!(WirelessFunction::try_from(0x01), Ok(WirelessFunction::Nack));
+        assert_eq!(
+            WirelessFunction::try_from(0x02),
+            Err(WirelessFrameError::InvalidFunction { byte: 0x02 })
+        );
+        for byte in [0x00, 0x01, 0x44, 0x46, 0x47, 0x48, 0x06, 0x53, 0x08] {
+            assert_eq!(u8::from(&WirelessFunction::try_from(byte).unwrap()), byte);
+        }
+    }
+
+    #[test]
+    fn test_extended_link_layer() {
+        let data = [0x8C, 0x20, 0x2A, 0x7A];
+        assert_eq!(
+            ExtendedLinkLayer::parse(&data),
+            Ok((
+                Some(ExtendedLinkLayer {
+                    communication_control: CommunicationControl::SYNCHRONIZED,
+                    access_number: 0x2A,
+                    address: None,
+                    session_number: None,
+                    payload_crc: None,
+                }),
+                [0x7A].as_slice()
+            ))
+        );
+
+        let payload = [0x7A, 0x01, 0x00, 0x00, 0x00];
+        let crc = crc16(&payload).to_le_bytes();
+        let data = [
+            0x8F, 0x00, 0x01, 0xE6, 0x1E, 0x78, 0x56, 0x34, 0x12, 0x01, 0x07, 0x13, 0x00, 0x00,
+            0x20, crc[0], crc[1], 0x7A, 0x01, 0x00, 0x00, 0x00,
+        ];
+        let (Some(ell), rest) = ExtendedLinkLayer::parse(&data).unwrap() else {
+            panic!("expected an extended link layer");
+        };
+        assert_eq!(rest, &payload);
+        assert_eq!(
+            ell.address,
+            Some(SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07))
+        );
+        assert_eq!(
+            ell.session_number,
+            Some(SessionNumber {
+                encryption: 1,
+                time: 1,
+                session: 3
+            })
+        );
+        assert!(ell.verify_payload_crc(rest));
+        assert!(!ell.verify_payload_crc(&rest[1..]));
+
+        assert_eq!(
+            ExtendedLinkLayer::parse(&data[..10]),
+            Err(WirelessFrameError::LengthShort)
+        );
+        assert_eq!(
+            ExtendedLinkLayer::parse(&payload),
+            Ok((None, payload.as_slice()))
+        );
+    }
+}
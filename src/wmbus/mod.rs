@@ -0,0 +1,368 @@
+//! is part of the wireless M-Bus (EN 13757-4) data link layer
+//! Telegrams are received with CRCs interleaved in the data, which are checked and removed
+//! before the link layer fields and the application layer data are parsed.
+mod crc;
+mod link_layer;
+
+pub use crc::crc16;
+pub use link_layer::{CommunicationControl, ExtendedLinkLayer, SessionNumber, WirelessFunction};
+
+use crate::user_data::{ApplicationLayerError, SecondaryAddress, UserDataBlock};
+
+/// Length of the first block: L, C, M and A field
+const FIRST_BLOCK_LENGTH: usize = 10;
+const CRC_LENGTH: usize = 2;
+/// Data bytes per block after the first one in frame format A
+const FORMAT_A_BLOCK_LENGTH: usize = 16;
+/// Data bytes of the second block in frame format B, the CI field and 115 bytes
+const FORMAT_B_SECOND_BLOCK_LENGTH: usize = 116;
+/// Largest telegram without CRCs, the L field plus 255 bytes
+pub const MAX_WIRELESS_FRAME_LENGTH: usize = 256;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameFormat {
+    /// A CRC follows the first 10 bytes and every further block of 16 bytes
+    A,
+    /// A single CRC over the first 126 bytes and another one over the rest, if any
+    B,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WirelessFrameError {
+    EmptyData,
+    LengthShort,
+    WrongLengthIndication,
+    InvalidFunction {
+        byte: u8,
+    },
+    WrongCrc {
+        block: usize,
+        expected: u16,
+        actual: u16,
+    },
+    BufferTooSmall {
+        required: usize,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for WirelessFrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WirelessFrameError::EmptyData => write!(f, "Data is empty"),
+            WirelessFrameError::LengthShort => write!(f, "Length mismatch"),
+            WirelessFrameError::WrongLengthIndication => write!(f, "Wrong length indication"),
+            WirelessFrameError::InvalidFunction { byte } => {
+                write!(f, "Invalid function: {}", byte)
+            }
+            WirelessFrameError::WrongCrc {
+                block,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Wrong CRC in block {}, expected: {:04X}, actual: {:04X}",
+                block, expected, actual
+            ),
+            WirelessFrameError::BufferTooSmall { required } => {
+                write!(f, "Buffer too small, required: {}", required)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WirelessFrameError {}
+
+/// Checks the CRCs of a received telegram and copies it without them into `buffer`.
+///
+/// The L field written to `buffer` counts the bytes following it without CRCs in both frame
+/// formats. Returns the number of bytes written.
+pub fn remove_crc(
+    data: &[u8],
+    format: FrameFormat,
+    buffer: &mut [u8],
+) -> Result<usize, WirelessFrameError> {
+    let length = usize::from(*data.first().ok_or(WirelessFrameError::EmptyData)?);
+    let mut written = 0;
+    // copies a block and returns the offset after its CRC
+    let mut copy_block = |block: usize, range: core::ops::Range<usize>| {
+        let end = range.end;
+        let block_data = data.get(range).ok_or(WirelessFrameError::LengthShort)?;
+        let crc_bytes = data
+            .get(end..end + CRC_LENGTH)
+            .and_then(|bytes| <[u8; CRC_LENGTH]>::try_from(bytes).ok())
+            .ok_or(WirelessFrameError::LengthShort)?;
+        let expected = u16::from_be_bytes(crc_bytes);
+        let actual = crc16(block_data);
+        if expected != actual {
+            return Err(WirelessFrameError::WrongCrc {
+                block,
+                expected,
+                actual,
+            });
+        }
+        let required = written + block_data.len();
+        buffer
+            .get_mut(written..required)
+            .ok_or(WirelessFrameError::BufferTooSmall { required })?
+            .copy_from_slice(block_data);
+        written = required;
+        Ok(end + CRC_LENGTH)
+    };
+
+    let (end, stripped_length) = match format {
+        FrameFormat::A => {
+            // the L field does not count the CRCs
+            if length < FIRST_BLOCK_LENGTH - 1 {
+                return Err(WirelessFrameError::WrongLengthIndication);
+            }
+            let mut offset = copy_block(0, 0..FIRST_BLOCK_LENGTH)?;
+            let mut remaining = length + 1 - FIRST_BLOCK_LENGTH;
+            let mut block = 1;
+            while remaining > 0 {
+                let size = remaining.min(FORMAT_A_BLOCK_LENGTH);
+                offset = copy_block(block, offset..offset + size)?;
+                remaining -= size;
+                block += 1;
+            }
+            (offset, length)
+        }
+        FrameFormat::B => {
+            // the L field counts the CRCs, the first CRC covers the first and second block
+            let end = length + 1;
+            let second_block_end = FIRST_BLOCK_LENGTH + FORMAT_B_SECOND_BLOCK_LENGTH;
+            if end < FIRST_BLOCK_LENGTH + CRC_LENGTH {
+                return Err(WirelessFrameError::WrongLengthIndication);
+            }
+            if end <= second_block_end + CRC_LENGTH {
+                copy_block(0, 0..end - CRC_LENGTH)?;
+                (end, length - CRC_LENGTH)
+            } else {
+                let offset = copy_block(0, 0..second_block_end)?;
+                if end < offset + CRC_LENGTH {
+                    return Err(WirelessFrameError::WrongLengthIndication);
+                }
+                copy_block(1, offset..end - CRC_LENGTH)?;
+                (end, length - 2 * CRC_LENGTH)
+            }
+        }
+    };
+
+    if data.len() != end {
+        return Err(WirelessFrameError::WrongLengthIndication);
+    }
+    // the length fits as it is smaller than the received one
+    *buffer
+        .first_mut()
+        .ok_or(WirelessFrameError::BufferTooSmall { required: 1 })? = stripped_length as u8;
+    Ok(written)
+}
+
+/// A wireless M-Bus telegram without CRCs
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WirelessFrame<'a> {
+    pub function: WirelessFunction,
+    /// Manufacturer (M field), identification number, version and device type (A field)
+    /// of the sender. This is the address of the repeater or converter if one forwarded the
+    /// telegram, the meter address is then found in the extended link layer or the long
+    /// transport layer header.
+    pub address: SecondaryAddress,
+    pub extended_link_layer: Option<ExtendedLinkLayer>,
+    /// Transport and application layer data, starting with the CI field
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    pub data: &'a [u8],
+}
+
+impl<'a> WirelessFrame<'a> {
+    /// Checks and removes the CRCs of `data` into `buffer` and parses the result
+    pub fn from_raw(
+        data: &[u8],
+        format: FrameFormat,
+        buffer: &'a mut [u8],
+    ) -> Result<Self, WirelessFrameError> {
+        let length = remove_crc(data, format, buffer)?;
+        Self::try_from(
+            buffer
+                .get(..length)
+                .ok_or(WirelessFrameError::LengthShort)?,
+        )
+    }
+
+    /// Parses the application layer data
+    pub fn user_data(&self) -> Result<UserDataBlock<'a>, ApplicationLayerError> {
+        UserDataBlock::try_from(self.data)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for WirelessFrame<'a> {
+    type Error = WirelessFrameError;
+
+    /// Parses a telegram without CRCs, e.g. as written by [`remove_crc`]
+    fn try_from(data: &'a [u8]) -> Result<Self, WirelessFrameError> {
+        let length = usize::from(*data.first().ok_or(WirelessFrameError::EmptyData)?);
+        if data.len() != length + 1 {
+            return Err(WirelessFrameError::WrongLengthIndication);
+        }
+        let header = data
+            .get(1..FIRST_BLOCK_LENGTH)
+            .and_then(|header| <[u8; FIRST_BLOCK_LENGTH - 1]>::try_from(header).ok())
+            .ok_or(WirelessFrameError::LengthShort)?;
+        let address = SecondaryAddress::from_bytes([
+            header[3], header[4], header[5], header[6], header[1], header[2], header[7], header[8],
+        ]);
+        let (extended_link_layer, data) = ExtendedLinkLayer::parse(
+            data.get(FIRST_BLOCK_LENGTH..)
+                .ok_or(WirelessFrameError::LengthShort)?,
+        )?;
+        Ok(Self {
+            function: WirelessFunction::try_from(header[0])?,
+            address,
+            extended_link_layer,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inserts the CRCs of frame format A into a telegram given without them
+    fn add_crc(data: &[u8], output: &mut [u8]) -> usize {
+        let mut written = 0;
+        let mut push = |bytes: &[u8], output: &mut [u8]| {
+            output[written..written + bytes.len()].copy_from_slice(bytes);
+            written += bytes.len();
+        };
+        push(&data[..10], output);
+        push(&crc16(&data[..10]).to_be_bytes(), output);
+        for block in data[10..].chunks(16) {
+            push(block, output);
+            push(&crc16(block).to_be_bytes(), output);
+        }
+        written
+    }
+
+    fn telegram(records: usize, output: &mut [u8; 256]) -> usize {
+        let header = [
+            0x44, 0xE6, 0x1E, 0x78, 0x56, 0x34, 0x12, 0x01, 0x07, 0x72, 0x78, 0x56, 0x34, 0x12,
+            0xE6, 0x1E, 0x01, 0x07, 0x2A, 0x00, 0x00, 0x00,
+        ];
+        let length = 1 + header.len() + records;
+        output[0] = (length - 1) as u8;
+        output[1..=header.len()].copy_from_slice(&header);
+        for (i, byte) in output[1 + header.len()..length].iter_mut().enumerate() {
+            // idle fillers followed by a single byte volume record
+            *byte = if i + 3 < records {
+                0x2F
+            } else {
+                [0x01, 0x13, 0x05][i + 3 - records]
+            };
+        }
+        length
+    }
+
+    #[test]
+    fn test_frame_format_a() {
+        let mut plain = [0u8; 256];
+        let length = telegram(20, &mut plain);
+        let mut raw = [0u8; 300];
+        let raw_length = add_crc(&plain[..length], &mut raw);
+        // 10 bytes + CRC, 2 * (16 bytes + CRC), 1 byte + CRC
+        assert_eq!(raw_length, length + 8);
+
+        let mut buffer = [0u8; MAX_WIRELESS_FRAME_LENGTH];
+        let frame =
+            WirelessFrame::from_raw(&raw[..raw_length], FrameFormat::A, &mut buffer).unwrap();
+        assert_eq!(frame.function, WirelessFunction::SndNr);
+        assert_eq!(frame.extended_link_layer, None);
+        assert_eq!(
+            frame.address,
+            SecondaryAddress::new(0x12345678, 0x1EE6, 0x01, 0x07)
+        );
+        assert_eq!(frame.data, &plain[10..length]);
+
+        let Ok(UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        }) = frame.user_data()
+        else {
+            panic!("expected a variable data structure");
+        };
+        assert_eq!(fixed_data_header.access_number, 0x2A);
+        assert_eq!(
+            crate::user_data::DataRecords::from(variable_data_block).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_frame_format_b() {
+        let mut plain = [0u8; 256];
+        let length = telegram(118, &mut plain);
+        // L field counting both CRCs, CRC after the L, C, M and A field, the CI field and
+        // 115 bytes, CRC after the third block
+        let mut raw = [0u8; 145];
+        raw[0] = 0x90;
+        raw[1..126].copy_from_slice(&plain[1..126]);
+        raw[126..128].copy_from_slice(&[0x35, 0xD1]);
+        raw[128..143].copy_from_slice(&plain[126..length]);
+        raw[143..].copy_from_slice(&[0x2E, 0x81]);
+
+        let mut buffer = [0u8; MAX_WIRELESS_FRAME_LENGTH];
+        let stripped = remove_crc(&raw, FrameFormat::B, &mut buffer).unwrap();
+        assert_eq!(&buffer[..stripped], &plain[..length]);
+
+        // a short telegram has a single CRC
+        let length = telegram(10, &mut plain);
+        let mut raw = [0u8; 35];
+        raw[..length].copy_from_slice(&plain[..length]);
+        raw[0] = 0x22;
+        let crc = crc16(&raw[..length]).to_be_bytes();
+        raw[length..].copy_from_slice(&crc);
+        let stripped = remove_crc(&raw, FrameFormat::B, &mut buffer).unwrap();
+        assert_eq!(&buffer[..stripped], &plain[..length]);
+
+        raw[12] ^= 0x01;
+        assert!(matches!(
+            remove_crc(&raw, FrameFormat::B, &mut buffer),
+            Err(WirelessFrameError::WrongCrc { block: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_wrong_crc() {
+        let mut plain = [0u8; 256];
+        let length = telegram(20, &mut plain);
+        let mut raw = [0u8; 300];
+        let raw_length = add_crc(&plain[..length], &mut raw);
+        let mut buffer = [0u8; MAX_WIRELESS_FRAME_LENGTH];
+
+        raw[15] ^= 0x01;
+        assert!(matches!(
+            remove_crc(&raw[..raw_length], FrameFormat::A, &mut buffer),
+            Err(WirelessFrameError::WrongCrc { block: 1, .. })
+        ));
+        raw[15] ^= 0x01;
+
+        assert_eq!(
+            remove_crc(&raw[..raw_length - 1], FrameFormat::A, &mut buffer),
+            Err(WirelessFrameError::LengthShort)
+        );
+        assert_eq!(
+            remove_crc(&raw[..raw_length], FrameFormat::A, &mut buffer[..10]),
+            Err(WirelessFrameError::BufferTooSmall { required: 26 })
+        );
+        assert_eq!(
+            remove_crc(&[], FrameFormat::B, &mut buffer),
+            Err(WirelessFrameError::EmptyData)
+        );
+    }
+}
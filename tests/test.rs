@@ -39,6 +39,20 @@ pub struct SlaveInformation {
     signature: Option<String>,
 }
 
+#[cfg(feature = "std")]
+#[derive(Debug, Deserialize)]
+pub struct MBusErrorData {
+    #[serde(rename = "SlaveInformation")]
+    slave_information: ErrorInformation,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Deserialize)]
+pub struct ErrorInformation {
+    #[serde(rename = "Error")]
+    error: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DataRecord {
     #[serde(rename = "id")]
@@ -101,7 +115,7 @@ mod tests {
         for entry in WalkDir::new("./tests/rscada/test-frames")
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "hex"))
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "hex"))
         {
             let contents =
                 fs::read_to_string(entry.path()).expect("Something went wrong reading the file");
@@ -151,7 +165,7 @@ mod tests {
                         u8::from_str_radix(&mbus_data.slave_information.status, 16).unwrap()
                     );
                     assert_eq!(
-                        fixed_data_header.signature,
+                        u16::from(&fixed_data_header.configuration_field),
                         u16::from_str_radix(
                             mbus_data.slave_information.signature.unwrap().as_str(),
                             16
@@ -175,4 +189,36 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_error_frame_parsing() {
+        for entry in WalkDir::new("./tests/rscada/error-frames")
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "xml"))
+        {
+            let xml_content =
+                fs::read_to_string(entry.path()).expect("Something went wrong reading the file");
+            let error_data: MBusErrorData = from_str(&xml_content).unwrap();
+            let contents = fs::read_to_string(entry.path().with_extension("hex"))
+                .expect("Something went wrong reading the file");
+            println!("Path: {}", entry.path().display());
+
+            let bytes = hex::decode(contents.trim().replace(' ', "")).unwrap();
+            let frame = Frame::try_from(bytes.as_slice()).unwrap();
+            let Frame::LongFrame { data, .. } = frame else {
+                panic!("Frame is not a long frame");
+            };
+            let UserDataBlock::ApplicationError { error, .. } =
+                UserDataBlock::try_from(data).unwrap()
+            else {
+                panic!("expected an application error");
+            };
+            assert_eq!(
+                error.to_string(),
+                error_data.slave_information.error.replace(['Â', '´'], "")
+            );
+        }
+    }
 }